@@ -0,0 +1,37 @@
+//! Compares incremental re-formatting with a full parse on a large document.
+//! Both should stay well under a 16ms frame budget.
+
+use core::buffer::{Buffer, Edit, FormattedBuffer};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+const SAMPLE: &str = include_str!("../../content/sample.md");
+
+fn large_buffer() -> Buffer {
+    Buffer::new(SAMPLE.repeat(200))
+}
+
+fn formatting(c: &mut Criterion) {
+    let buffer = large_buffer();
+    let formatted = FormattedBuffer::from_buffer(&buffer);
+    let offset = buffer.line_to_byte(buffer.len_lines() / 2);
+    let edit = Edit::insert(offset, "**bold** ");
+
+    let mut edited = buffer.clone();
+    edited.apply(&edit);
+
+    c.bench_function("full parse", |b| {
+        b.iter(|| FormattedBuffer::from_buffer(&edited))
+    });
+
+    c.bench_function("incremental update", |b| {
+        b.iter_batched(
+            || formatted.clone(),
+            |mut formatted| formatted.update(&edited, &edit),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, formatting);
+criterion_main!(benches);
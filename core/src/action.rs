@@ -2,7 +2,8 @@ use std::{path::PathBuf, sync::Arc};
 
 use crate::{
     document::{DocumentHandler, DocumentId},
-    pane::{Pane, PaneId},
+    encoding::TextFormat,
+    pane::{Axis, GroupId, Pane, PaneId},
 };
 
 #[derive(Debug, Clone)]
@@ -11,11 +12,33 @@ pub enum PaneAction {
     Open(PaneId),
     Add(Pane),
     Replace(PaneId, Pane),
+    /// Move the pane to the index among tabs of its group
+    Move {
+        id: PaneId,
+        index: usize,
+    },
+    /// Move the pane to the end of another tab group
+    MoveToSplit {
+        id: PaneId,
+        group: GroupId,
+    },
+    /// Split the focused tab group, showing its open pane in the new group
+    Split(Axis),
+    /// Close the focused tab group with its panes
+    CloseSplit,
+    FocusNextSplit,
+    FocusPreviousSplit,
+    /// Reopen the most recently closed pane at its place
+    ReopenClosed,
+    /// Grow the focused tab group by a share of its split, shrinking it with a negative one
+    Resize(f32),
 }
 
 #[derive(Debug, Clone)]
 pub enum FileAction {
     PickFile,
+    /// Create an untitled document that isn't saved anywhere yet
+    NewFile,
     OpenFileCurrentTab(PathBuf),
     OpenFileForceCurrentTab(PathBuf),
     OpenFileNewTab(PathBuf),
@@ -26,7 +49,17 @@ pub enum DocumentAction {
     Add(Arc<DocumentHandler<String>>),
     Open(DocumentId),
     Save(DocumentId),
+    /// Ask for a file and save the document there
+    SaveAs(DocumentId),
+    /// Save the document to the file, without asking
+    SaveTo(DocumentId, PathBuf),
+    /// Save every changed document, asking for files of untitled ones
+    SaveAll,
     Remove(DocumentId),
+    /// Export document to a standalone HTML file
+    ExportHtml(DocumentId),
+    /// Change encoding and line endings the document is saved with
+    SetFormat(DocumentId, TextFormat),
 }
 
 #[derive(Debug, Clone)]
@@ -1,20 +1,207 @@
-use crate::Color;
+use std::{borrow::Cow, fmt, ops::Range};
 
-#[derive(Debug, Clone)]
+use ropey::Rope;
+
+use crate::{markdown, search::SearchQuery};
+
+/// How many times an incremental update may grow the reparsed region
+const MAX_UPDATE_ATTEMPTS: usize = 4;
+
+/// Text buffer backed by a rope.
+///
+/// All offsets are byte offsets and must lie on char boundaries.
+/// Offset conversions and edits take `O(log n)` time,
+/// cloning a buffer is `O(1)` and yields an independent snapshot.
+#[derive(Debug, Default, Clone)]
 pub struct Buffer {
-    pub text: String,
+    rope: Rope,
 }
 
 impl Buffer {
-    pub fn new(text: impl Into<String>) -> Self {
-        Self { text: text.into() }
+    pub fn new(text: impl AsRef<str>) -> Self {
+        Self {
+            rope: Rope::from_str(text.as_ref()),
+        }
+    }
+
+    /// Returns a cheap copy of the current buffer state
+    pub fn snapshot(&self) -> Buffer {
+        self.clone()
+    }
+
+    /// Length of the buffer in bytes
+    pub fn len(&self) -> usize {
+        self.rope.len_bytes()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rope.len_bytes() == 0
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.rope.len_chars()
+    }
+
+    /// Number of lines, a trailing line break starts an empty last line
+    pub fn len_lines(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    pub fn insert(&mut self, offset: usize, text: &str) {
+        let char_index = self.rope.byte_to_char(offset);
+        self.rope.insert(char_index, text);
+    }
+
+    pub fn delete(&mut self, range: Range<usize>) {
+        let start = self.rope.byte_to_char(range.start);
+        let end = self.rope.byte_to_char(range.end);
+        self.rope.remove(start..end);
+    }
+
+    pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        let start = range.start;
+        self.delete(range);
+        self.insert(start, text);
+    }
+
+    pub fn byte_to_char(&self, offset: usize) -> usize {
+        self.rope.byte_to_char(offset)
+    }
+
+    pub fn char_to_byte(&self, index: usize) -> usize {
+        self.rope.char_to_byte(index)
+    }
+
+    pub fn byte_to_line(&self, offset: usize) -> usize {
+        self.rope.byte_to_line(offset)
+    }
+
+    pub fn line_to_byte(&self, line: usize) -> usize {
+        self.rope.line_to_byte(line)
+    }
+
+    pub fn char_to_line(&self, index: usize) -> usize {
+        self.rope.char_to_line(index)
+    }
+
+    pub fn line_to_char(&self, line: usize) -> usize {
+        self.rope.line_to_char(line)
+    }
+
+    /// Returns text of the line including its line break
+    pub fn line(&self, line: usize) -> Cow<'_, str> {
+        self.rope.line(line).into()
+    }
+
+    /// Iterates over lines without line breaks, like `str::lines`
+    pub fn lines(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        let count = if self.rope.line(self.rope.len_lines() - 1).len_bytes() == 0 {
+            self.rope.len_lines() - 1
+        } else {
+            self.rope.len_lines()
+        };
+
+        self.rope.lines().take(count).map(|line| {
+            let line: Cow<'_, str> = line.into();
+            match line {
+                Cow::Borrowed(line) => Cow::Borrowed(trim_line_break(line)),
+                Cow::Owned(line) => Cow::Owned(trim_line_break(&line).to_string()),
+            }
+        })
+    }
+
+    /// Returns text in the given byte range
+    pub fn slice(&self, range: Range<usize>) -> Cow<'_, str> {
+        self.rope.byte_slice(range).into()
+    }
+
+    /// Returns the whole text, borrowed if it is stored contiguously
+    pub fn text(&self) -> Cow<'_, str> {
+        (&self.rope).into()
+    }
+
+    pub fn apply(&mut self, edit: &Edit) {
+        self.replace(edit.range.clone(), &edit.text);
+    }
+
+    /// Returns byte ranges of all query matches
+    pub fn find(&self, query: &SearchQuery) -> Result<Vec<Range<usize>>, String> {
+        Ok(query.build()?.find_all(&self.text()))
+    }
+
+    /// Replaces all query matches, returning the number of replacements
+    pub fn replace_all(&mut self, query: &SearchQuery, replacement: &str) -> Result<usize, String> {
+        let (text, count) = query.build()?.replace_all(&self.text(), replacement);
+        if count > 0 {
+            self.rope = Rope::from_str(&text);
+        }
+        Ok(count)
+    }
+}
+
+/// Replacement of a byte range of a `Buffer` with new text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl Edit {
+    pub fn new(range: Range<usize>, text: impl Into<String>) -> Self {
+        Self {
+            range,
+            text: text.into(),
+        }
     }
 
-    pub fn lines(&self) -> impl Iterator<Item = &str> {
-        self.text.lines()
+    pub fn insert(offset: usize, text: impl Into<String>) -> Self {
+        Self::new(offset..offset, text)
     }
+
+    pub fn delete(range: Range<usize>) -> Self {
+        Self::new(range, String::new())
+    }
+
+    /// Edit turning `old` text into `new`, replacing the part between
+    /// their common prefix and suffix
+    pub fn between(old: &str, new: &str) -> Self {
+        let prefix = common_len(old.chars(), new.chars());
+        let suffix = common_len(old[prefix..].chars().rev(), new[prefix..].chars().rev());
+        Self::new(prefix..old.len() - suffix, &new[prefix..new.len() - suffix])
+    }
+
+    /// Difference between the buffer length after and before the edit
+    pub fn delta(&self) -> isize {
+        self.text.len() as isize - self.range.len() as isize
+    }
+}
+
+/// Byte length of the common start of two char sequences
+fn common_len(a: impl Iterator<Item = char>, b: impl Iterator<Item = char>) -> usize {
+    a.zip(b)
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum()
+}
+
+fn trim_line_break(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
 }
 
+impl fmt::Display for Buffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.rope.fmt(f)
+    }
+}
+
+impl From<&str> for Buffer {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Parsed representation of a Markdown `Buffer`
 #[derive(Debug, Clone)]
 pub struct FormattedBuffer {
     pub elements: Vec<Element>,
@@ -22,34 +209,415 @@ pub struct FormattedBuffer {
 
 impl FormattedBuffer {
     pub fn from_buffer(buffer: &Buffer) -> Self {
-        let elements = buffer
-            .lines()
-            .map(|line| {
-                Element::Span(
-                    line.to_string(),
-                    SpanAttributes {
-                        font_size: 16.0,
-                        font_color: Color::BLACK,
-                    },
-                )
-            })
-            .collect();
-
-        Self { elements }
+        Self {
+            elements: markdown::parse(&buffer.text()),
+        }
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &Element> {
         self.elements.iter()
     }
+
+    /// Updates elements after `edit` was applied to `buffer`.
+    ///
+    /// Only top-level blocks touched by the edit and their direct neighbours
+    /// are parsed again, following blocks are just shifted. When the edit
+    /// changes how following blocks parse, like an unclosed code fence
+    /// swallowing them, the region grows until it's parsed till the end.
+    pub fn update(&mut self, buffer: &Buffer, edit: &Edit) {
+        let count = self.elements.len();
+        let first = self
+            .elements
+            .partition_point(|element| element.range.end < edit.range.start)
+            .saturating_sub(1);
+        let last = (self
+            .elements
+            .partition_point(|element| element.range.start <= edit.range.end)
+            + 1)
+        .min(count);
+
+        if first >= last {
+            *self = Self::from_buffer(buffer);
+            return;
+        }
+
+        let delta = edit.delta();
+        let line_start = |offset: usize| buffer.line_to_byte(buffer.byte_to_line(offset));
+        let line_end = |offset: usize| {
+            let line = buffer.byte_to_line(offset);
+            if buffer.line_to_byte(line) == offset {
+                offset
+            } else {
+                buffer.line_to_byte(line + 1)
+            }
+        };
+
+        // Region starts on a line boundary not shared with preceding blocks
+        let mut first = first;
+        let mut start = line_start(self.elements[first].range.start.min(edit.range.start));
+        while first > 0 && self.elements[first - 1].range.end > start {
+            first -= 1;
+            start = line_start(self.elements[first].range.start);
+        }
+        if first == 0 {
+            start = 0;
+        }
+
+        // Region is parsed together with two following blocks. Once the first
+        // of them comes out unchanged, the region doesn't depend on the rest
+        // of the document. Otherwise the region grows a few times before
+        // falling back to parsing till the end.
+        let mut last = last;
+        let mut attempts = 0;
+        let elements = loop {
+            if last + 2 > count || attempts == MAX_UPDATE_ATTEMPTS {
+                last = count;
+                break parse_region(buffer, start..buffer.len());
+            }
+
+            let mut next = self.elements[last].clone();
+            next.shift(delta);
+            let after = &self.elements[last + 1].range;
+            let after_start = after.start.saturating_add_signed(delta);
+            let end = line_end(after.end.saturating_add_signed(delta));
+
+            let mut elements = parse_region(buffer, start..end);
+            let len = elements.len();
+            if len >= 2 && elements[len - 2] == next && elements[len - 1].range.start == after_start
+            {
+                elements.truncate(len - 2);
+                break elements;
+            }
+
+            last += 1;
+            attempts += 1;
+        };
+
+        for element in &mut self.elements[last..] {
+            element.shift(delta);
+        }
+        self.elements.splice(first..last, elements);
+    }
+
+    /// Returns the innermost block element containing given byte offset
+    pub fn element_at(&self, offset: usize) -> Option<&Element> {
+        let mut elements = &self.elements;
+        let mut found = None;
+
+        while let Some(element) = elements
+            .iter()
+            .find(|element| element.range.contains(&offset))
+        {
+            found = Some(element);
+            elements = match element.children() {
+                Some(children) => children,
+                None => break,
+            };
+        }
+
+        found
+    }
 }
 
-#[derive(Debug, Clone)]
-pub enum Element {
+/// Block level element
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    pub kind: ElementKind,
+    /// Byte range of the element in the source buffer
+    pub range: Range<usize>,
+}
+
+impl Element {
+    pub fn new(kind: ElementKind, range: Range<usize>) -> Self {
+        Self { kind, range }
+    }
+
+    /// Moves element and its descendants by `delta` bytes
+    pub(crate) fn shift(&mut self, delta: isize) {
+        shift_range(&mut self.range, delta);
+
+        match &mut self.kind {
+            ElementKind::Heading { content, .. } | ElementKind::Paragraph(content) => {
+                content.iter_mut().for_each(|inline| inline.shift(delta))
+            }
+            ElementKind::List {
+                items: children, ..
+            }
+            | ElementKind::Item(children)
+            | ElementKind::TaskItem { children, .. }
+            | ElementKind::BlockQuote(children) => {
+                children.iter_mut().for_each(|child| child.shift(delta))
+            }
+            ElementKind::Table { header, rows, .. } => {
+                for cell in header.iter_mut().chain(rows.iter_mut().flatten()) {
+                    shift_range(&mut cell.range, delta);
+                    cell.content
+                        .iter_mut()
+                        .for_each(|inline| inline.shift(delta));
+                }
+            }
+            ElementKind::CodeBlock { .. } | ElementKind::Rule => {}
+        }
+    }
+
+    /// Returns nested block elements if element is a container
+    pub fn children(&self) -> Option<&Vec<Element>> {
+        match &self.kind {
+            ElementKind::List { items, .. } => Some(items),
+            ElementKind::Item(children)
+            | ElementKind::TaskItem { children, .. }
+            | ElementKind::BlockQuote(children) => Some(children),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElementKind {
+    Heading {
+        level: u8,
+        content: Vec<Inline>,
+    },
+    Paragraph(Vec<Inline>),
+    /// List containing `Item` and `TaskItem` elements.
+    /// `start` is the number of the first item, always 1 for unordered lists.
+    List {
+        ordered: bool,
+        start: u64,
+        items: Vec<Element>,
+    },
+    Item(Vec<Element>),
+    TaskItem {
+        checked: bool,
+        children: Vec<Element>,
+    },
+    BlockQuote(Vec<Element>),
+    CodeBlock {
+        lang: Option<String>,
+        code: String,
+    },
+    Table {
+        alignments: Vec<Alignment>,
+        header: Vec<TableCell>,
+        rows: Vec<Vec<TableCell>>,
+    },
+    Rule,
+}
+
+/// Column alignment of a table
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    #[default]
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableCell {
+    pub content: Vec<Inline>,
+    pub range: Range<usize>,
+}
+
+/// Inline element
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inline {
+    pub kind: InlineKind,
+    /// Byte range of the element in the source buffer
+    pub range: Range<usize>,
+}
+
+impl Inline {
+    pub fn new(kind: InlineKind, range: Range<usize>) -> Self {
+        Self { kind, range }
+    }
+
+    fn shift(&mut self, delta: isize) {
+        shift_range(&mut self.range, delta);
+
+        if let InlineKind::Link { content, .. } = &mut self.kind {
+            content.iter_mut().for_each(|inline| inline.shift(delta));
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InlineKind {
+    /// Run of text sharing the same attributes
     Span(String, SpanAttributes),
+    Link {
+        target: String,
+        content: Vec<Inline>,
+    },
+    Image {
+        src: String,
+        alt: String,
+    },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct SpanAttributes {
-    pub font_size: f32,
-    pub font_color: Color,
+    pub bold: bool,
+    pub italic: bool,
+    pub strike: bool,
+    pub monospace: bool,
+}
+
+fn shift_range(range: &mut Range<usize>, delta: isize) {
+    range.start = range.start.saturating_add_signed(delta);
+    range.end = range.end.saturating_add_signed(delta);
+}
+
+/// Parses part of the buffer, keeping element ranges relative to the whole buffer
+fn parse_region(buffer: &Buffer, range: Range<usize>) -> Vec<Element> {
+    let offset = range.start as isize;
+    let text = buffer.slice(range);
+    let mut elements = if offset == 0 {
+        markdown::parse(&text)
+    } else {
+        markdown::parse_fragment(&text)
+    };
+    for element in &mut elements {
+        element.shift(offset);
+    }
+    elements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits() {
+        let mut buffer = Buffer::new("Hello world");
+        buffer.insert(5, ",");
+        assert_eq!(buffer.text(), "Hello, world");
+
+        buffer.delete(5..6);
+        assert_eq!(buffer.text(), "Hello world");
+
+        buffer.replace(6..11, "мир");
+        assert_eq!(buffer.text(), "Hello мир");
+        assert_eq!(buffer.len(), 12);
+        assert_eq!(buffer.len_chars(), 9);
+
+        buffer.insert(buffer.len(), "!");
+        assert_eq!(buffer.to_string(), "Hello мир!");
+    }
+
+    #[test]
+    fn offset_conversions() {
+        let buffer = Buffer::new("ab\nвгд\n\nе");
+        assert_eq!(buffer.len_lines(), 4);
+
+        assert_eq!(buffer.line_to_byte(1), 3);
+        assert_eq!(buffer.line_to_byte(3), 11);
+        assert_eq!(buffer.byte_to_line(5), 1);
+        assert_eq!(buffer.byte_to_char(5), 4);
+        assert_eq!(buffer.char_to_byte(4), 5);
+        assert_eq!(buffer.char_to_line(7), 2);
+        assert_eq!(buffer.line_to_char(3), 8);
+
+        assert_eq!(buffer.line(1), "вгд\n");
+        assert_eq!(buffer.slice(3..7), "вг");
+    }
+
+    #[test]
+    fn lines() {
+        let buffer = Buffer::new("one\r\ntwo\n\nfour\n");
+        let lines: Vec<_> = buffer.lines().collect();
+        assert_eq!(lines, vec!["one", "two", "", "four"]);
+        assert_eq!(buffer.lines().count(), buffer.text().lines().count());
+        assert_eq!(Buffer::new("").lines().count(), 0);
+    }
+
+    #[test]
+    fn snapshots_are_independent() {
+        let mut buffer = Buffer::new("text");
+        let snapshot = buffer.snapshot();
+        buffer.insert(0, "more ");
+
+        assert_eq!(snapshot.text(), "text");
+        assert_eq!(buffer.text(), "more text");
+    }
+
+    #[test]
+    fn search() {
+        let mut buffer = Buffer::new("мир and Мир\nмирный");
+        let query = SearchQuery::new("мир").whole_word(true);
+        assert_eq!(buffer.find(&query), Ok(vec![0..6, 11..17]));
+
+        assert_eq!(buffer.replace_all(&query, "world"), Ok(2));
+        assert_eq!(buffer.text(), "world and world\nмирный");
+        assert!(buffer.find(&SearchQuery::new("[").regex(true)).is_err());
+    }
+
+    #[test]
+    fn edit_between() {
+        assert_eq!(Edit::between("hello", "help"), Edit::new(3..5, "p"));
+        assert_eq!(Edit::between("мир", "мир!"), Edit::insert(6, "!"));
+        assert_eq!(Edit::between("aaa", "aa"), Edit::delete(2..3));
+        assert_eq!(Edit::between("same", "same"), Edit::insert(4, ""));
+
+        let mut buffer = Buffer::new("one\ntwo\n");
+        buffer.apply(&Edit::between("one\ntwo\n", "one\nthree\n"));
+        assert_eq!(buffer.text(), "one\nthree\n");
+    }
+
+    /// Applies pseudo-random edits and compares incremental result with a full parse
+    #[test]
+    fn incremental_update_matches_full_parse() {
+        const SNIPPETS: [&str; 10] = [
+            "\n",
+            "\n\n",
+            "# ",
+            "- ",
+            "```\n",
+            "> ",
+            "**bold** ",
+            "text",
+            "---\n",
+            "1. ",
+        ];
+
+        let mut buffer = Buffer::new(include_str!("../../content/sample.md"));
+        let mut formatted = FormattedBuffer::from_buffer(&buffer);
+        let mut seed: u64 = 42;
+        let mut random = |bound: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % bound.max(1)
+        };
+
+        for _ in 0..500 {
+            let text = buffer.text().into_owned();
+            let boundary = |offset: usize| {
+                (0..=offset)
+                    .rev()
+                    .find(|offset| text.is_char_boundary(*offset))
+                    .unwrap_or(0)
+            };
+
+            let start = boundary(random(text.len() + 1));
+            let end = boundary((start + random(8)).min(text.len()));
+            let replacement = if random(3) == 0 {
+                ""
+            } else {
+                SNIPPETS[random(SNIPPETS.len())]
+            };
+
+            let edit = Edit::new(start..end, replacement);
+            buffer.apply(&edit);
+            formatted.update(&buffer, &edit);
+
+            assert_eq!(
+                formatted.elements,
+                FormattedBuffer::from_buffer(&buffer).elements,
+                "after {edit:?} in {:?}",
+                buffer.text()
+            );
+        }
+    }
 }
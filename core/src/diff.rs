@@ -0,0 +1,179 @@
+//! Line diff of two texts
+
+/// How a line differs between the old and the new text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub change: Change,
+    pub text: String,
+}
+
+/// Line diff of two texts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diff {
+    Lines(Vec<DiffLine>),
+    /// Texts have too many changed lines to be compared
+    TooLarge,
+}
+
+/// Most pairs of changed lines compared, bigger diffs take too long to be useful
+const LIMIT: usize = 16_000_000;
+
+/// Diffs texts line by line, keeping the longest common subsequence of lines unchanged
+pub fn lines(old: &str, new: &str) -> Diff {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Common prefix and suffix are skipped, so typical edits compare only a few lines
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+    if a.len().saturating_mul(b.len()) > LIMIT {
+        return Diff::TooLarge;
+    }
+
+    let mut diff: Vec<DiffLine> = old[..prefix]
+        .iter()
+        .map(|text| line(Change::Same, text))
+        .collect();
+    hirschberg(a, b, &mut diff);
+    diff.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|text| line(Change::Same, text)),
+    );
+
+    Diff::Lines(diff)
+}
+
+fn line(change: Change, text: &str) -> DiffLine {
+    DiffLine {
+        change,
+        text: text.to_string(),
+    }
+}
+
+/// Diffs in linear space by splitting `a` in half and finding where the
+/// common subsequence crosses `b` at that point
+fn hirschberg(a: &[&str], b: &[&str], diff: &mut Vec<DiffLine>) {
+    match a {
+        [] => diff.extend(b.iter().map(|text| line(Change::Added, text))),
+        [single] => match b.iter().position(|text| text == single) {
+            Some(j) => {
+                diff.extend(b[..j].iter().map(|text| line(Change::Added, text)));
+                diff.push(line(Change::Same, single));
+                diff.extend(b[j + 1..].iter().map(|text| line(Change::Added, text)));
+            }
+            None => {
+                diff.push(line(Change::Removed, single));
+                diff.extend(b.iter().map(|text| line(Change::Added, text)));
+            }
+        },
+        _ if b.is_empty() => diff.extend(a.iter().map(|text| line(Change::Removed, text))),
+        _ => {
+            let middle = a.len() / 2;
+            let head = lengths(a[..middle].iter(), b.iter());
+            let tail = lengths(a[middle..].iter().rev(), b.iter().rev());
+
+            // First split of `b` with the longest common subsequence
+            let split = (0..=b.len())
+                .rev()
+                .max_by_key(|&j| head[j] + tail[b.len() - j])
+                .unwrap_or(0);
+            hirschberg(&a[..middle], &b[..split], diff);
+            hirschberg(&a[middle..], &b[split..], diff);
+        }
+    }
+}
+
+/// Lengths of the longest common subsequences of `a` and each prefix of `b`
+fn lengths<'a>(
+    a: impl Iterator<Item = &'a &'a str>,
+    b: impl Iterator<Item = &'a &'a str> + Clone,
+) -> Vec<usize> {
+    let mut row = vec![0; b.clone().count() + 1];
+    for x in a {
+        let mut diagonal = 0;
+        for (j, y) in b.clone().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if x == y {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_lines() {
+        let Diff::Lines(diff) = lines("a\nb\nc\nd\n", "a\nc\nx\nd\ne\n") else {
+            panic!("diff is small");
+        };
+        let diff: Vec<(Change, &str)> = diff
+            .iter()
+            .map(|line| (line.change, line.text.as_str()))
+            .collect();
+
+        assert_eq!(
+            diff,
+            vec![
+                (Change::Same, "a"),
+                (Change::Removed, "b"),
+                (Change::Same, "c"),
+                (Change::Added, "x"),
+                (Change::Same, "d"),
+                (Change::Added, "e"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_longest_common_lines() {
+        let old = "x\na\nb\nc\ny\nd\n";
+        let new = "a\nz\nb\nd\nc\nw\n";
+        let Diff::Lines(diff) = lines(old, new) else {
+            panic!("diff is small");
+        };
+
+        let same = diff
+            .iter()
+            .filter(|line| line.change == Change::Same)
+            .count();
+        assert_eq!(same, 3);
+        let text = |change| {
+            diff.iter()
+                .filter(|line| line.change != change)
+                .map(|line| line.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        assert_eq!(text(Change::Added) + "\n", old);
+        assert_eq!(text(Change::Removed) + "\n", new);
+    }
+
+    #[test]
+    fn too_large() {
+        let old: String = (0..5000).map(|i| format!("{i}\n")).collect();
+        let new: String = (0..5000).map(|i| format!("-{i}\n")).collect();
+        assert_eq!(lines(&old, &new), Diff::TooLarge);
+    }
+}
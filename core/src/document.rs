@@ -1,18 +1,46 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::{encoding::TextFormat, frontmatter::Frontmatter, history::History};
 
 pub type DocumentId = usize;
 
 #[derive(Default, Debug)]
 pub struct DocumentHandler<Content> {
     pub text_content: Content,
-    pub path: PathBuf,
+    /// File of the document, `None` for untitled documents that were never saved
+    pub path: Option<PathBuf>,
     pub filename: String,
     pub changed: bool,
+    /// Time of the last edit that wasn't saved yet
+    pub edited_at: Option<Instant>,
+    /// Text of the file as it was last read or written
+    pub saved_text: String,
+    /// Encoding and line endings the file is saved with
+    pub format: TextFormat,
+    pub history: History<String>,
+    /// Incremented on every change of the text
+    pub revision: usize,
+    /// Frontmatter of the document, kept in sync with the text
+    pub metadata: Option<Frontmatter>,
+}
+
+impl<Content> DocumentHandler<Content> {
+    /// Title from the frontmatter, if any
+    pub fn title(&self) -> Option<&str> {
+        self.metadata.as_ref().and_then(|metadata| metadata.title())
+    }
 }
 
 #[derive(Default, Debug)]
 pub struct DocumentStore<Content> {
     documents: HashMap<DocumentId, DocumentHandler<Content>>,
+    /// Documents by canonical path of their file
+    paths: HashMap<PathBuf, DocumentId>,
     next_id: DocumentId,
 }
 
@@ -20,13 +48,27 @@ impl<Content> DocumentStore<Content> {
     pub fn new() -> Self {
         Self {
             documents: HashMap::new(),
+            paths: HashMap::new(),
             next_id: 1,
         }
     }
 
+    /// Adds the document, unless a document of the same file is already open.
+    /// Returns id of the added or the already open document.
     pub fn add(&mut self, document: DocumentHandler<Content>) -> DocumentId {
+        if let Some(id) = document
+            .path
+            .as_deref()
+            .and_then(|path| self.find_by_path(path))
+        {
+            return id;
+        }
+
         let id = self.next_id;
 
+        if let Some(path) = &document.path {
+            self.paths.insert(canonical(path), id);
+        }
         self.documents.insert(id, document);
         self.next_id += 1;
 
@@ -34,6 +76,7 @@ impl<Content> DocumentStore<Content> {
     }
 
     pub fn remove(&mut self, id: &DocumentId) -> Option<DocumentHandler<Content>> {
+        self.paths.retain(|_, document| document != id);
         self.documents.remove(id)
     }
 
@@ -48,4 +91,76 @@ impl<Content> DocumentStore<Content> {
     pub fn get_mut(&mut self, id: &DocumentId) -> Option<&mut DocumentHandler<Content>> {
         self.documents.get_mut(id)
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&DocumentId, &DocumentHandler<Content>)> {
+        self.documents.iter()
+    }
+
+    /// Finds an open document of the file
+    pub fn find_by_path(&self, path: &Path) -> Option<DocumentId> {
+        self.paths.get(&canonical(path)).copied()
+    }
+
+    /// Moves the document to another file, also used to give a file to an untitled document
+    pub fn set_path(&mut self, id: &DocumentId, path: PathBuf) {
+        let Some(document) = self.documents.get_mut(id) else {
+            return;
+        };
+
+        self.paths.retain(|_, document| document != id);
+        self.paths.insert(canonical(&path), *id);
+        document.path = Some(path);
+    }
+
+    /// Ids of untitled documents
+    pub fn untitled(&self) -> impl Iterator<Item = DocumentId> + '_ {
+        self.documents
+            .iter()
+            .filter(|(_, document)| document.path.is_none())
+            .map(|(id, _)| *id)
+    }
+}
+
+/// Resolves links and relative parts of the path, keeping it as is if the file doesn't exist
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedup_by_path() {
+        let directory = std::env::temp_dir();
+        let document = |path: Option<PathBuf>| DocumentHandler::<String> {
+            path,
+            ..Default::default()
+        };
+
+        let mut store = DocumentStore::new();
+        let id = store.add(document(Some(directory.join("note.md"))));
+        assert_eq!(
+            store.add(document(Some(directory.join(".").join("note.md")))),
+            id
+        );
+        assert_eq!(store.count(), 1);
+
+        // Untitled documents are never merged
+        let first = store.add(document(None));
+        let second = store.add(document(None));
+        assert_ne!(first, second);
+        assert_eq!(store.untitled().count(), 2);
+
+        store.set_path(&first, directory.join("saved.md"));
+        assert_eq!(store.find_by_path(&directory.join("saved.md")), Some(first));
+        assert_eq!(store.untitled().collect::<Vec<_>>(), vec![second]);
+
+        store.set_path(&id, directory.join("renamed.md"));
+        assert_eq!(store.find_by_path(&directory.join("note.md")), None);
+        assert_eq!(store.find_by_path(&directory.join("renamed.md")), Some(id));
+
+        store.remove(&id);
+        assert_eq!(store.find_by_path(&directory.join("renamed.md")), None);
+    }
 }
@@ -0,0 +1,320 @@
+//! Text encodings and line endings of files on disk
+
+use std::fmt;
+
+use crate::diff::{self, Change, Diff};
+
+/// Character encoding of a file
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    /// UTF-8 starting with a byte order mark
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// UTF-16 LE without a byte order mark
+    Utf16LeNoBom,
+    /// UTF-16 BE without a byte order mark
+    Utf16BeNoBom,
+    /// ISO-8859-1, used for files that aren't valid UTF-8
+    Latin1,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 7] = [
+        Encoding::Utf8,
+        Encoding::Utf8Bom,
+        Encoding::Utf16Le,
+        Encoding::Utf16Be,
+        Encoding::Utf16LeNoBom,
+        Encoding::Utf16BeNoBom,
+        Encoding::Latin1,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf8Bom => "UTF-8 BOM",
+            Encoding::Utf16Le => "UTF-16 LE",
+            Encoding::Utf16Be => "UTF-16 BE",
+            Encoding::Utf16LeNoBom => "UTF-16 LE no BOM",
+            Encoding::Utf16BeNoBom => "UTF-16 BE no BOM",
+            Encoding::Latin1 => "Latin-1",
+        }
+    }
+
+    /// Next encoding in `ALL`, wrapping around
+    pub fn next(&self) -> Self {
+        let i = Self::ALL.iter().position(|e| e == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Line separator of a file
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+    /// Both separators, each line keeps the one it was read with
+    Mixed,
+}
+
+impl LineEnding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+            LineEnding::Mixed => "Mixed",
+        }
+    }
+
+    /// Switches between LF and CRLF, mixed endings become LF
+    pub fn toggle(&self) -> Self {
+        match self {
+            LineEnding::Lf => LineEnding::CrLf,
+            LineEnding::CrLf | LineEnding::Mixed => LineEnding::Lf,
+        }
+    }
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How text of a file is stored on disk
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextFormat {
+    pub encoding: Encoding,
+    pub line_ending: LineEnding,
+}
+
+/// Character that can't be written in the chosen encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodeError {
+    pub encoding: Encoding,
+    pub char: char,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} can't be written in {}", self.char, self.encoding)
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// Detects format of the file contents and decodes them into text with `\n` line endings.
+/// Text with both LF and CRLF endings is left as it is.
+pub fn decode(bytes: &[u8]) -> (String, TextFormat) {
+    let (text, encoding) = if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        (
+            String::from_utf8_lossy(rest).into_owned(),
+            Encoding::Utf8Bom,
+        )
+    } else if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        (utf16(rest, u16::from_le_bytes), Encoding::Utf16Le)
+    } else if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        (utf16(rest, u16::from_be_bytes), Encoding::Utf16Be)
+    } else if let Some(encoding) = utf16_without_bom(bytes) {
+        let unit = match encoding {
+            Encoding::Utf16LeNoBom => u16::from_le_bytes,
+            _ => u16::from_be_bytes,
+        };
+        (utf16(bytes, unit), encoding)
+    } else {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => (text.to_string(), Encoding::Utf8),
+            // Every byte is a valid Latin-1 character, so this never fails
+            Err(_) => (bytes.iter().map(|&b| b as char).collect(), Encoding::Latin1),
+        }
+    };
+
+    // Lone carriage returns are left as they are
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    let (text, line_ending) = match (crlf, lf) {
+        (0, _) => (text, LineEnding::Lf),
+        (_, 0) => (text.replace("\r\n", "\n"), LineEnding::CrLf),
+        _ => (text, LineEnding::Mixed),
+    };
+
+    (
+        text,
+        TextFormat {
+            encoding,
+            line_ending,
+        },
+    )
+}
+
+/// Encodes text with `\n` line endings into file contents of the format.
+/// Text of mixed format is written with the endings it has, see `keep_endings`.
+pub fn encode(text: &str, format: &TextFormat) -> Result<Vec<u8>, EncodeError> {
+    let text = match format.line_ending {
+        LineEnding::Lf => text.replace("\r\n", "\n"),
+        LineEnding::CrLf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+        LineEnding::Mixed => text.to_string(),
+    };
+
+    let bytes = match format.encoding {
+        Encoding::Utf8 => text.into_bytes(),
+        Encoding::Utf8Bom => [b"\xEF\xBB\xBF".as_slice(), text.as_bytes()].concat(),
+        Encoding::Utf16Le => [0xFF, 0xFE]
+            .into_iter()
+            .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect(),
+        Encoding::Utf16Be => [0xFE, 0xFF]
+            .into_iter()
+            .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+            .collect(),
+        Encoding::Utf16LeNoBom => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        Encoding::Utf16BeNoBom => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        Encoding::Latin1 => text
+            .chars()
+            .map(|c| {
+                u8::try_from(c).map_err(|_| EncodeError {
+                    encoding: Encoding::Latin1,
+                    char: c,
+                })
+            })
+            .collect::<Result<_, _>>()?,
+    };
+
+    Ok(bytes)
+}
+
+/// Gives CRLF endings back to the lines of the edited text that are unchanged
+/// since the `original` text with mixed endings, other lines end with LF
+pub fn keep_endings(text: &str, original: &str) -> String {
+    let Diff::Lines(lines) = diff::lines(original, text) else {
+        return text.to_string();
+    };
+
+    let mut endings =
+        original
+            .split_inclusive('\n')
+            .map(|line| if line.ends_with("\r\n") { "\r\n" } else { "\n" });
+    let mut kept = String::with_capacity(text.len());
+    for line in lines {
+        let ending = match line.change {
+            Change::Same => endings.next().unwrap_or("\n"),
+            Change::Removed => {
+                endings.next();
+                continue;
+            }
+            Change::Added => "\n",
+        };
+        kept.push_str(&line.text);
+        kept.push_str(ending);
+    }
+    kept
+}
+
+/// Most bytes looked at to guess the encoding
+const SAMPLE: usize = 4096;
+
+/// Guesses UTF-16 without a byte order mark from NUL bytes, which pad
+/// ASCII characters there and hardly ever appear in other text
+fn utf16_without_bom(bytes: &[u8]) -> Option<Encoding> {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let pairs = bytes[..bytes.len().min(SAMPLE)].chunks_exact(2);
+    let count = pairs.len();
+    let (high, low) = pairs.fold((0, 0), |(high, low), pair| {
+        (
+            high + usize::from(pair[0] == 0),
+            low + usize::from(pair[1] == 0),
+        )
+    });
+
+    // Most characters of a Markdown file are ASCII, whatever the language
+    if low * 2 >= count && high * 8 < low {
+        Some(Encoding::Utf16LeNoBom)
+    } else if high * 2 >= count && low * 8 < high {
+        Some(Encoding::Utf16BeNoBom)
+    } else {
+        None
+    }
+}
+
+fn utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let files: [&[u8]; 7] = [
+            b"# Note\nline\n",
+            b"\xEF\xBB\xBF# Note\r\nline\r\n",
+            b"\xFF\xFE#\x00 \x00\xE9\x00\r\x00\n\x00",
+            b"\xFE\xFF\x00#\x00 \x00\xE9\x00\n",
+            b"#\x00 \x00N\x00o\x00t\x00e\x00\n\x00",
+            b"\x00#\x00 \x00N\x00o\x00t\x00e\x00\n",
+            b"caf\xE9\r\n",
+        ];
+
+        for bytes in files {
+            let (text, format) = decode(bytes);
+            assert!(!text.contains('\r'));
+            assert_eq!(encode(&text, &format).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn detection() {
+        let (text, format) = decode(b"caf\xE9\r\nau lait\r\n");
+        assert_eq!(text, "café\nau lait\n");
+        assert_eq!(format.encoding, Encoding::Latin1);
+        assert_eq!(format.line_ending, LineEnding::CrLf);
+
+        let (text, format) = decode(b"#\x00 \x00\xE9\x00\n\x00");
+        assert_eq!(text, "# é\n");
+        assert_eq!(format.encoding, Encoding::Utf16LeNoBom);
+        // A stray NUL doesn't make UTF-8 look like UTF-16
+        assert_eq!(decode(b"hello\x00world\n").1.encoding, Encoding::Utf8);
+
+        let format = TextFormat {
+            encoding: Encoding::Latin1,
+            line_ending: LineEnding::Lf,
+        };
+        assert_eq!(
+            encode("→", &format),
+            Err(EncodeError {
+                encoding: Encoding::Latin1,
+                char: '→'
+            })
+        );
+    }
+
+    #[test]
+    fn mixed_line_endings() {
+        let bytes = b"a\r\nb\nc\r\n";
+        let (original, format) = decode(bytes);
+        assert_eq!(original, "a\r\nb\nc\r\n");
+        assert_eq!(format.line_ending, LineEnding::Mixed);
+        assert_eq!(encode(&original, &format).unwrap(), bytes);
+
+        // Editor drops the endings, so they are given back to unchanged lines
+        let edited = keep_endings("a\nx\nc\nd\n", &original);
+        assert_eq!(edited, "a\r\nx\nc\r\nd\n");
+    }
+}
@@ -0,0 +1,210 @@
+//! Export of formatted documents to other formats
+
+use std::fmt::Write;
+
+use crate::buffer::{Alignment, Element, ElementKind, FormattedBuffer, Inline, InlineKind};
+
+/// Renders the document to a standalone HTML page with embedded stylesheet
+pub fn to_html(buffer: &FormattedBuffer, title: &str, css: &str) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>{}</title>", escape(title));
+    let _ = writeln!(html, "<style>\n{css}</style>");
+    html.push_str("</head>\n<body>\n");
+    blocks(&mut html, &buffer.elements);
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn blocks(html: &mut String, elements: &[Element]) {
+    for element in elements {
+        block(html, element);
+    }
+}
+
+fn block(html: &mut String, element: &Element) {
+    match &element.kind {
+        ElementKind::Heading { level, content } => {
+            let _ = write!(html, "<h{level}>");
+            inlines(html, content);
+            let _ = writeln!(html, "</h{level}>");
+        }
+        ElementKind::Paragraph(content) => {
+            html.push_str("<p>");
+            inlines(html, content);
+            html.push_str("</p>\n");
+        }
+        ElementKind::List {
+            ordered,
+            start,
+            items,
+        } => {
+            let tag = if *ordered { "ol" } else { "ul" };
+            if *ordered && *start != 1 {
+                let _ = writeln!(html, "<ol start=\"{start}\">");
+            } else {
+                let _ = writeln!(html, "<{tag}>");
+            }
+            blocks(html, items);
+            let _ = writeln!(html, "</{tag}>");
+        }
+        ElementKind::Item(children) => {
+            html.push_str("<li>");
+            blocks(html, children);
+            html.push_str("</li>\n");
+        }
+        ElementKind::TaskItem { checked, children } => {
+            let checked = if *checked { " checked" } else { "" };
+            let _ = write!(
+                html,
+                "<li class=\"task\"><input type=\"checkbox\" disabled{checked}>"
+            );
+            blocks(html, children);
+            html.push_str("</li>\n");
+        }
+        ElementKind::BlockQuote(children) => {
+            html.push_str("<blockquote>\n");
+            blocks(html, children);
+            html.push_str("</blockquote>\n");
+        }
+        ElementKind::CodeBlock { lang, code } => code_block(html, lang.as_deref(), code),
+        ElementKind::Table {
+            alignments,
+            header,
+            rows,
+        } => {
+            html.push_str("<table>\n<thead>\n<tr>");
+            for (i, cell) in header.iter().enumerate() {
+                let _ = write!(html, "<th{}>", align(alignments.get(i)));
+                inlines(html, &cell.content);
+                html.push_str("</th>");
+            }
+            html.push_str("</tr>\n</thead>\n<tbody>\n");
+            for row in rows {
+                html.push_str("<tr>");
+                for (i, cell) in row.iter().enumerate() {
+                    let _ = write!(html, "<td{}>", align(alignments.get(i)));
+                    inlines(html, &cell.content);
+                    html.push_str("</td>");
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</tbody>\n</table>\n");
+        }
+        ElementKind::Rule => html.push_str("<hr>\n"),
+    }
+}
+
+fn code_block(html: &mut String, lang: Option<&str>, code: &str) {
+    match lang {
+        Some(lang) => {
+            let _ = write!(html, "<pre><code class=\"language-{}\">", escape(lang));
+        }
+        None => html.push_str("<pre><code>"),
+    }
+
+    #[cfg(feature = "highlight")]
+    let tokens = lang.and_then(|lang| crate::highlight::highlight(code, lang));
+    #[cfg(not(feature = "highlight"))]
+    let tokens: Option<crate::highlight::Tokens> = None;
+
+    match tokens {
+        Some(tokens) => {
+            for (range, token) in tokens {
+                let text = escape(&code[range]);
+                if token == crate::highlight::Token::Plain {
+                    html.push_str(&text);
+                } else {
+                    let _ = write!(html, "<span class=\"tok-{}\">{text}</span>", token.name());
+                }
+            }
+        }
+        None => html.push_str(&escape(code)),
+    }
+
+    html.push_str("</code></pre>\n");
+}
+
+fn inlines(html: &mut String, content: &[Inline]) {
+    for inline in content {
+        match &inline.kind {
+            InlineKind::Span(text, attributes) => {
+                let tags = [
+                    (attributes.monospace, "code"),
+                    (attributes.bold, "strong"),
+                    (attributes.italic, "em"),
+                    (attributes.strike, "del"),
+                ];
+                for (_, tag) in tags.iter().filter(|(enabled, _)| *enabled) {
+                    let _ = write!(html, "<{tag}>");
+                }
+                html.push_str(&escape(text));
+                for (_, tag) in tags.iter().rev().filter(|(enabled, _)| *enabled) {
+                    let _ = write!(html, "</{tag}>");
+                }
+            }
+            InlineKind::Link { target, content } => {
+                let _ = write!(html, "<a href=\"{}\">", escape(target));
+                inlines(html, content);
+                html.push_str("</a>");
+            }
+            InlineKind::Image { src, alt } => {
+                let _ = write!(
+                    html,
+                    "<img src=\"{}\" alt=\"{}\">",
+                    escape(src),
+                    escape(alt)
+                );
+            }
+        }
+    }
+}
+
+fn align(alignment: Option<&Alignment>) -> &'static str {
+    match alignment {
+        Some(Alignment::Left) => " style=\"text-align: left\"",
+        Some(Alignment::Center) => " style=\"text-align: center\"",
+        Some(Alignment::Right) => " style=\"text-align: right\"",
+        Some(Alignment::None) | None => "",
+    }
+}
+
+/// Escapes HTML special characters
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+
+    #[test]
+    fn document() {
+        let buffer = Buffer::new(
+            "# Title & more\n\nSome **bold** and [link](https://example.com).\n\n\
+             - [x] done\n\n```\n<tag>\n```\n",
+        );
+        let html = to_html(&FormattedBuffer::from_buffer(&buffer), "Note", "p { }\n");
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Note</title>"));
+        assert!(html.contains("<style>\np { }\n</style>"));
+        assert!(html.contains("<h1>Title &amp; more</h1>"));
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(html.contains("<a href=\"https://example.com\">link</a>"));
+        assert!(html.contains("<input type=\"checkbox\" disabled checked>"));
+        assert!(html.contains("<pre><code>&lt;tag&gt;\n</code></pre>"));
+    }
+}
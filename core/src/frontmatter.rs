@@ -0,0 +1,310 @@
+//! YAML and TOML frontmatter of markdown documents
+
+use std::ops::Range;
+
+use smol_str::SmolStr;
+
+use crate::value::Value;
+
+/// Frontmatter syntax, chosen by its delimiter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Block delimited by `---`
+    Yaml,
+    /// Block delimited by `+++`
+    Toml,
+}
+
+impl Format {
+    pub fn delimiter(&self) -> &'static str {
+        match self {
+            Format::Yaml => "---",
+            Format::Toml => "+++",
+        }
+    }
+}
+
+/// Metadata block at the start of a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frontmatter {
+    pub format: Format,
+    /// Fields in the order they appear in the block
+    pub fields: Vec<(SmolStr, Value)>,
+    /// Byte range of the block in the document, including delimiters
+    pub range: Range<usize>,
+}
+
+impl Frontmatter {
+    /// Parses frontmatter at the start of the text.
+    /// Returns `None` if there is no block or it's malformed.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (format, body, range) = split(text)?;
+        let fields = match format {
+            Format::Yaml => from_yaml(body)?,
+            Format::Toml => from_toml(body)?,
+        };
+
+        Some(Self {
+            format,
+            fields,
+            range,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Sets field value, keeping its position if it already exists
+    pub fn set(&mut self, key: impl Into<SmolStr>, value: Value) {
+        let key = key.into();
+        match self.fields.iter_mut().find(|(name, _)| *name == key) {
+            Some((_, old)) => *old = value,
+            None => self.fields.push((key, value)),
+        }
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        match self.get("title")? {
+            Value::String(title) => Some(title.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Tags given either as a list or a comma separated string
+    pub fn tags(&self) -> Vec<&str> {
+        self.strings("tags")
+    }
+
+    pub fn aliases(&self) -> Vec<&str> {
+        self.strings("aliases")
+    }
+
+    pub fn created(&self) -> Option<&Value> {
+        self.get("created")
+    }
+
+    fn strings(&self, key: &str) -> Vec<&str> {
+        match self.get(key) {
+            Some(Value::List(values)) => values
+                .iter()
+                .filter_map(|value| match value {
+                    Value::String(s) => Some(s.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            Some(Value::String(s)) => s
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Serializes fields back into a delimited block
+    pub fn to_text(&self) -> String {
+        let body = match self.format {
+            Format::Yaml => to_yaml(&self.fields),
+            Format::Toml => to_toml(&self.fields),
+        };
+        let delimiter = self.format.delimiter();
+        format!("{delimiter}\n{body}{delimiter}\n")
+    }
+
+    /// Replaces the frontmatter block in the document text with the serialized fields
+    pub fn write(&self, text: &str) -> String {
+        let range = match split(text) {
+            Some((_, _, range)) => range,
+            None => 0..0,
+        };
+        let mut result = String::with_capacity(text.len());
+        result.push_str(&text[..range.start]);
+        result.push_str(&self.to_text());
+        result.push_str(&text[range.end..]);
+        result
+    }
+}
+
+/// Byte range of the delimited block at the start of the text, even if its fields don't parse
+pub fn span(text: &str) -> Option<Range<usize>> {
+    split(text).map(|(_, _, range)| range)
+}
+
+/// Finds the delimited block, returning its format, body and range
+fn split(text: &str) -> Option<(Format, &str, Range<usize>)> {
+    let first = text.lines().next()?;
+    let format = match first.trim_end() {
+        "---" => Format::Yaml,
+        "+++" => Format::Toml,
+        _ => return None,
+    };
+
+    let body_start = text.find('\n')? + 1;
+    let mut offset = body_start;
+    for line in text[body_start..].split_inclusive('\n') {
+        if line.trim_end() == format.delimiter() {
+            return Some((format, &text[body_start..offset], 0..offset + line.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn from_yaml(body: &str) -> Option<Vec<(SmolStr, Value)>> {
+    let mapping: serde_yaml::Mapping = if body.trim().is_empty() {
+        serde_yaml::Mapping::new()
+    } else {
+        serde_yaml::from_str(body).ok()?
+    };
+
+    Some(
+        mapping
+            .into_iter()
+            .filter_map(|(key, value)| Some((SmolStr::new(key.as_str()?), yaml_value(value)?)))
+            .collect(),
+    )
+}
+
+fn yaml_value(value: serde_yaml::Value) -> Option<Value> {
+    match value {
+        serde_yaml::Value::Bool(b) => Some(Value::Boolean(b)),
+        serde_yaml::Value::Number(n) => match n.as_i64() {
+            Some(i) => Some(Value::Integer(i)),
+            None => n.as_f64().map(Value::Float),
+        },
+        serde_yaml::Value::String(s) => Some(Value::String(SmolStr::new(s))),
+        serde_yaml::Value::Sequence(values) => Some(Value::List(
+            values.into_iter().filter_map(yaml_value).collect(),
+        )),
+        serde_yaml::Value::Tagged(tagged) => yaml_value(tagged.value),
+        serde_yaml::Value::Null | serde_yaml::Value::Mapping(_) => None,
+    }
+}
+
+fn to_yaml(fields: &[(SmolStr, Value)]) -> String {
+    let mapping: serde_yaml::Mapping = fields
+        .iter()
+        .map(|(key, value)| (serde_yaml::Value::from(key.as_str()), yaml(value)))
+        .collect();
+
+    if mapping.is_empty() {
+        String::new()
+    } else {
+        serde_yaml::to_string(&mapping).unwrap_or_default()
+    }
+}
+
+fn yaml(value: &Value) -> serde_yaml::Value {
+    match value {
+        Value::Integer(i) => (*i).into(),
+        Value::Float(f) => (*f).into(),
+        Value::Boolean(b) => (*b).into(),
+        Value::Color(color) => String::from(*color).into(),
+        Value::Path(path) => path.to_string_lossy().as_ref().into(),
+        Value::String(s) => s.as_str().into(),
+        Value::List(values) => values.iter().map(yaml).collect(),
+    }
+}
+
+fn from_toml(body: &str) -> Option<Vec<(SmolStr, Value)>> {
+    let table: toml::Table = toml::from_str(body).ok()?;
+
+    Some(
+        table
+            .into_iter()
+            .filter_map(|(key, value)| Some((SmolStr::new(key), toml_value(value)?)))
+            .collect(),
+    )
+}
+
+fn toml_value(value: toml::Value) -> Option<Value> {
+    match value {
+        toml::Value::String(s) => Some(Value::String(SmolStr::new(s))),
+        toml::Value::Integer(i) => Some(Value::Integer(i)),
+        toml::Value::Float(f) => Some(Value::Float(f)),
+        toml::Value::Boolean(b) => Some(Value::Boolean(b)),
+        // Dates are kept as text, the same way YAML exposes them
+        toml::Value::Datetime(date) => Some(Value::String(SmolStr::new(date.to_string()))),
+        toml::Value::Array(values) => Some(Value::List(
+            values.into_iter().filter_map(toml_value).collect(),
+        )),
+        toml::Value::Table(_) => None,
+    }
+}
+
+fn to_toml(fields: &[(SmolStr, Value)]) -> String {
+    let table: toml::Table = fields
+        .iter()
+        .map(|(key, value)| (key.to_string(), toml(value)))
+        .collect();
+
+    toml::to_string(&table).unwrap_or_default()
+}
+
+fn toml(value: &Value) -> toml::Value {
+    match value {
+        Value::Integer(i) => (*i).into(),
+        Value::Float(f) => (*f).into(),
+        Value::Boolean(b) => (*b).into(),
+        Value::Color(color) => String::from(*color).into(),
+        Value::Path(path) => path.to_string_lossy().as_ref().into(),
+        Value::String(s) => s.as_str().into(),
+        Value::List(values) => toml::Value::Array(values.iter().map(toml).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml() {
+        let text = "---\ntitle: Notes\ntags: [rust, editor]\ncount: 3\n---\n# Body\n";
+        let frontmatter = Frontmatter::parse(text).unwrap();
+
+        assert_eq!(frontmatter.format, Format::Yaml);
+        assert_eq!(frontmatter.title(), Some("Notes"));
+        assert_eq!(frontmatter.tags(), vec!["rust", "editor"]);
+        assert_eq!(frontmatter.get("count"), Some(&Value::Integer(3)));
+        assert_eq!(&text[frontmatter.range.end..], "# Body\n");
+    }
+
+    #[test]
+    fn toml() {
+        let text = "+++\ntitle = \"Notes\"\ncreated = 2024-05-01\naliases = \"a, b\"\n+++\n";
+        let frontmatter = Frontmatter::parse(text).unwrap();
+
+        assert_eq!(frontmatter.format, Format::Toml);
+        assert_eq!(frontmatter.title(), Some("Notes"));
+        assert_eq!(frontmatter.aliases(), vec!["a", "b"]);
+        assert_eq!(
+            frontmatter.created(),
+            Some(&Value::String(SmolStr::new("2024-05-01")))
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "---\ntitle: Notes\ntags:\n- a\n- b\n---\nBody\n";
+        let mut frontmatter = Frontmatter::parse(text).unwrap();
+        assert_eq!(frontmatter.write(text), text);
+
+        frontmatter.set("title", Value::String(SmolStr::new("Renamed")));
+        let written = frontmatter.write(text);
+        assert!(written.ends_with("---\nBody\n"));
+        assert_eq!(
+            Frontmatter::parse(&written).unwrap().title(),
+            Some("Renamed")
+        );
+    }
+
+    #[test]
+    fn missing() {
+        assert_eq!(Frontmatter::parse("# Title\n"), None);
+        assert_eq!(Frontmatter::parse("---\ntitle: unclosed\n"), None);
+    }
+}
@@ -0,0 +1,221 @@
+//! Syntax highlighting of code snippets
+
+use std::ops::Range;
+
+/// Kind of a highlighted code token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Plain,
+    Comment,
+    Keyword,
+    String,
+    Number,
+    Constant,
+    Type,
+    Function,
+    Operator,
+    Variable,
+}
+
+impl Token {
+    pub const ALL: [Token; 10] = [
+        Token::Plain,
+        Token::Comment,
+        Token::Keyword,
+        Token::String,
+        Token::Number,
+        Token::Constant,
+        Token::Type,
+        Token::Function,
+        Token::Operator,
+        Token::Variable,
+    ];
+
+    /// Lowercase name of the token kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            Token::Plain => "plain",
+            Token::Comment => "comment",
+            Token::Keyword => "keyword",
+            Token::String => "string",
+            Token::Number => "number",
+            Token::Constant => "constant",
+            Token::Type => "type",
+            Token::Function => "function",
+            Token::Operator => "operator",
+            Token::Variable => "variable",
+        }
+    }
+}
+
+/// Token ranges in the highlighted text
+pub type Tokens = Vec<(Range<usize>, Token)>;
+
+#[cfg(feature = "highlight")]
+pub use highlighter::*;
+
+#[cfg(feature = "highlight")]
+mod highlighter {
+    use std::{ops::Range, sync::OnceLock};
+
+    use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxSet};
+
+    use super::{Token, Tokens};
+
+    /// Scope prefixes checked in order, so more specific ones go first
+    const SCOPES: [(&str, Token); 16] = [
+        ("comment", Token::Comment),
+        ("string", Token::String),
+        ("constant.numeric", Token::Number),
+        ("constant", Token::Constant),
+        ("keyword.operator", Token::Operator),
+        ("keyword", Token::Keyword),
+        ("storage.type.primitive", Token::Type),
+        ("storage", Token::Keyword),
+        ("entity.name.function", Token::Function),
+        ("support.function", Token::Function),
+        ("variable.function", Token::Function),
+        ("entity.name", Token::Type),
+        ("support.type", Token::Type),
+        ("support.class", Token::Type),
+        ("variable.language", Token::Keyword),
+        ("variable", Token::Variable),
+    ];
+
+    fn syntaxes() -> &'static SyntaxSet {
+        static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+        SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+    }
+
+    fn scopes() -> &'static [(Scope, Token)] {
+        static SCOPES_PARSED: OnceLock<Vec<(Scope, Token)>> = OnceLock::new();
+        SCOPES_PARSED.get_or_init(|| {
+            SCOPES
+                .iter()
+                .filter_map(|(scope, token)| Some((Scope::new(scope).ok()?, *token)))
+                .collect()
+        })
+    }
+
+    /// Line by line highlighter of a snippet in some language
+    #[derive(Debug, Clone)]
+    pub struct Highlighter {
+        state: ParseState,
+        stack: ScopeStack,
+    }
+
+    impl Highlighter {
+        /// Creates highlighter for a language name or extension like `rust` or `sh`
+        pub fn new(lang: &str) -> Option<Self> {
+            let syntax = syntaxes().find_syntax_by_token(lang)?;
+            Some(Self {
+                state: ParseState::new(syntax),
+                stack: ScopeStack::new(),
+            })
+        }
+
+        /// Highlights the next line of the snippet, returning token ranges within the line
+        pub fn highlight_line(&mut self, line: &str) -> Tokens {
+            let ops = if line.ends_with('\n') {
+                self.state.parse_line(line, syntaxes())
+            } else {
+                self.state.parse_line(&format!("{line}\n"), syntaxes())
+            };
+
+            let mut tokens = Vec::new();
+            let mut start = 0;
+            for (offset, op) in ops.unwrap_or_default() {
+                let offset = offset.min(line.len());
+                push_token(&mut tokens, start..offset, self.token());
+                start = offset;
+                let _ = self.stack.apply(&op);
+            }
+            push_token(&mut tokens, start..line.len(), self.token());
+
+            tokens
+        }
+
+        /// Token of the innermost recognized scope
+        fn token(&self) -> Token {
+            self.stack
+                .as_slice()
+                .iter()
+                .rev()
+                .find_map(|scope| {
+                    scopes()
+                        .iter()
+                        .find(|(prefix, _)| prefix.is_prefix_of(*scope))
+                        .map(|(_, token)| *token)
+                })
+                .unwrap_or(Token::Plain)
+        }
+    }
+
+    /// Highlights the whole snippet, returning token ranges within it.
+    /// Returns `None` if the language is unknown.
+    pub fn highlight(code: &str, lang: &str) -> Option<Tokens> {
+        let mut highlighter = Highlighter::new(lang)?;
+        let mut tokens = Vec::new();
+        let mut offset = 0;
+
+        for line in code.split_inclusive('\n') {
+            for (range, token) in highlighter.highlight_line(line) {
+                push_token(&mut tokens, range.start + offset..range.end + offset, token);
+            }
+            offset += line.len();
+        }
+
+        Some(tokens)
+    }
+
+    /// Appends token range, merging it with the previous one of the same kind
+    fn push_token(tokens: &mut Tokens, range: Range<usize>, token: Token) {
+        if range.is_empty() {
+            return;
+        }
+
+        match tokens.last_mut() {
+            Some((last, last_token)) if *last_token == token && last.end == range.start => {
+                last.end = range.end;
+            }
+            _ => tokens.push((range, token)),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "highlight"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_rust() {
+        let code = "fn main() {\n    let s = \"hi\"; // note\n}\n";
+        let tokens = highlight(code, "rust").unwrap();
+
+        let token_of = |text: &str| {
+            let start = code.find(text).unwrap();
+            tokens
+                .iter()
+                .find(|(range, _)| range.contains(&start))
+                .map(|(_, token)| *token)
+        };
+        assert_eq!(token_of("fn"), Some(Token::Keyword));
+        assert_eq!(token_of("main"), Some(Token::Function));
+        assert_eq!(token_of("\"hi\""), Some(Token::String));
+        assert_eq!(token_of("// note"), Some(Token::Comment));
+
+        // Tokens cover the whole snippet without gaps
+        assert_eq!(tokens.first().unwrap().0.start, 0);
+        assert_eq!(tokens.last().unwrap().0.end, code.len());
+        assert!(tokens
+            .windows(2)
+            .all(|pair| pair[0].0.end == pair[1].0.start));
+    }
+
+    #[test]
+    fn unknown_language() {
+        assert!(highlight("code", "no-such-language").is_none());
+        assert!(Highlighter::new("sh").is_some());
+        assert!(Highlighter::new("sql").is_some());
+    }
+}
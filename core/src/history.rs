@@ -0,0 +1,196 @@
+/// Kind of a recorded change, consecutive typing or deletion are grouped into one step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Typing,
+    Deletion,
+    Other,
+}
+
+/// Steps kept by default
+const LIMIT: usize = 1000;
+
+/// Size of snapshots kept by default, so large documents keep fewer steps
+const MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Undo/redo stack of document snapshots
+#[derive(Debug)]
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    last: Option<Change>,
+    /// Undo stack depth of the saved state, `None` if it can't be reached anymore
+    saved: Option<usize>,
+    limit: usize,
+    /// Total size of the stored snapshots
+    bytes: usize,
+    max_bytes: usize,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> History<T> {
+    pub fn new() -> Self {
+        Self::with_limits(LIMIT, MAX_BYTES)
+    }
+
+    /// Keeps at most `limit` steps, dropping the oldest ones while the snapshots
+    /// take more than `max_bytes`. The newest step is always kept.
+    pub fn with_limits(limit: usize, max_bytes: usize) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last: None,
+            saved: Some(0),
+            limit,
+            bytes: 0,
+            max_bytes,
+        }
+    }
+}
+
+impl<T: AsRef<str>> History<T> {
+    /// Records state before a change, the state is only taken when a new step starts
+    pub fn record(&mut self, change: Change, before: impl FnOnce() -> T) {
+        if self.saved.is_some_and(|saved| saved > self.undo.len()) {
+            self.saved = None;
+        }
+        self.bytes -= self
+            .redo
+            .drain(..)
+            .map(|snapshot| size(&snapshot))
+            .sum::<usize>();
+
+        let coalesce = self.last == Some(change) && change != Change::Other && !self.is_saved();
+        self.last = Some(change);
+        if coalesce {
+            return;
+        }
+
+        let before = before();
+        self.bytes += size(&before);
+        self.undo.push(before);
+
+        let over = |history: &Self| {
+            history.undo.len() > history.limit || history.bytes > history.max_bytes
+        };
+        let mut dropped = 0;
+        while self.undo.len() - dropped > 1 && over(self) {
+            self.bytes -= size(&self.undo[dropped]);
+            dropped += 1;
+        }
+        self.undo.drain(..dropped);
+        self.saved = self.saved.and_then(|saved| saved.checked_sub(dropped));
+    }
+
+    /// Starts a new step for the next change
+    pub fn break_group(&mut self) {
+        self.last = None;
+    }
+
+    /// Returns the previous state, storing the current one for redo
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.undo.pop()?;
+        self.bytes += size(&current);
+        self.bytes -= size(&previous);
+        self.redo.push(current);
+        self.last = None;
+        Some(previous)
+    }
+
+    /// Returns the next state, storing the current one for undo
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo.pop()?;
+        self.bytes += size(&current);
+        self.bytes -= size(&next);
+        self.undo.push(current);
+        self.last = None;
+        Some(next)
+    }
+}
+
+impl<T> History<T> {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Marks the current state as saved
+    pub fn mark_saved(&mut self) {
+        self.saved = Some(self.undo.len());
+        self.last = None;
+    }
+
+    /// Returns true if the current state matches the saved one
+    pub fn is_saved(&self) -> bool {
+        self.saved == Some(self.undo.len())
+    }
+}
+
+fn size<T: AsRef<str>>(snapshot: &T) -> usize {
+    snapshot.as_ref().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coalescing() {
+        let mut history = History::new();
+        history.record(Change::Typing, || "");
+        history.record(Change::Typing, || "a");
+        history.record(Change::Other, || "ab");
+        history.record(Change::Typing, || "ab\n");
+
+        assert_eq!(history.undo("ab\nc"), Some("ab\n"));
+        assert_eq!(history.undo("ab\n"), Some("ab"));
+        assert_eq!(history.undo("ab"), Some(""));
+        assert_eq!(history.undo(""), None);
+        assert_eq!(history.redo(""), Some("ab"));
+    }
+
+    #[test]
+    fn saved_state() {
+        let mut history = History::new();
+        assert!(history.is_saved());
+
+        history.record(Change::Typing, || "");
+        assert!(!history.is_saved());
+        history.mark_saved();
+
+        // Typing after a save starts a new step
+        history.record(Change::Typing, || "a");
+        assert!(!history.is_saved());
+        history.undo("ab");
+        assert!(history.is_saved());
+
+        // Saved state is lost once its redo branch is replaced
+        history.undo("a");
+        history.record(Change::Other, || "");
+        assert!(!history.is_saved());
+        history.undo("x");
+        assert!(!history.is_saved());
+    }
+
+    #[test]
+    fn size_limit() {
+        let mut history = History::with_limits(100, 10);
+        history.record(Change::Other, || "aaaa");
+        history.record(Change::Other, || "bbbb");
+        history.record(Change::Other, || "cccc");
+        assert_eq!(history.undo("dddd"), Some("cccc"));
+        assert_eq!(history.undo("cccc"), Some("bbbb"));
+        assert_eq!(history.undo("bbbb"), None);
+
+        // A snapshot bigger than the limit is still kept as the only step
+        history.record(Change::Other, || "a very long snapshot");
+        assert_eq!(history.undo(""), Some("a very long snapshot"));
+    }
+}
@@ -11,4 +11,5 @@ pub enum Modifiers {
     Ctrl,
     Alt,
     CtrlAlt,
+    CtrlShift,
 }
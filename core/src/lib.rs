@@ -1,10 +1,20 @@
 pub mod action;
 pub mod buffer;
 pub mod color;
+pub mod diff;
 pub mod document;
+pub mod encoding;
+pub mod export;
+pub mod frontmatter;
+pub mod highlight;
+pub mod history;
 pub mod hotkey;
+pub mod markdown;
 pub mod pane;
+pub mod recovery;
+pub mod search;
 pub mod value;
+pub mod vault;
 
 pub use color::*;
 pub use hotkey::*;
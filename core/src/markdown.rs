@@ -0,0 +1,1018 @@
+use std::ops::Range;
+
+use pulldown_cmark::{CodeBlockKind, Event, LinkType, OffsetIter, Options, Parser, Tag, TagEnd};
+
+use crate::buffer::{
+    Alignment, Element, ElementKind, Inline, InlineKind, SpanAttributes, TableCell,
+};
+
+/// Parses CommonMark text with GFM extensions into a list of block elements.
+/// Frontmatter at the start of the text is skipped.
+pub fn parse(text: &str) -> Vec<Element> {
+    let Some(span) = crate::frontmatter::span(text) else {
+        return parse_fragment(text);
+    };
+    let mut elements = parse_fragment(&text[span.end..]);
+    for element in &mut elements {
+        element.shift(span.end as isize);
+    }
+    elements
+}
+
+/// Parses a part of a document that doesn't start it, so `---` there is never frontmatter
+pub fn parse_fragment(text: &str) -> Vec<Element> {
+    let mut builder = Builder {
+        events: Parser::new_ext(text, options()).into_offset_iter(),
+        task: None,
+    };
+    builder.blocks(None)
+}
+
+fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_WIKILINKS
+}
+
+struct Builder<'a> {
+    events: OffsetIter<'a>,
+    /// Task marker of the list item being parsed
+    task: Option<bool>,
+}
+
+impl Builder<'_> {
+    /// Collects block elements until `end` tag is reached.
+    ///
+    /// Inline content outside of paragraph (e.g. items of tight lists)
+    /// is wrapped into an implicit paragraph.
+    fn blocks(&mut self, end: Option<TagEnd>) -> Vec<Element> {
+        let mut elements = Vec::new();
+        let mut inlines = Vec::new();
+
+        while let Some((event, range)) = self.events.next() {
+            let kind = match event {
+                Event::End(tag) if Some(tag) == end => break,
+                Event::Start(Tag::Paragraph) => {
+                    ElementKind::Paragraph(self.inlines(TagEnd::Paragraph))
+                }
+                Event::Start(Tag::Heading { level, .. }) => ElementKind::Heading {
+                    level: level as u8,
+                    content: self.inlines(TagEnd::Heading(level)),
+                },
+                Event::Start(Tag::BlockQuote(kind)) => {
+                    ElementKind::BlockQuote(self.blocks(Some(TagEnd::BlockQuote(kind))))
+                }
+                Event::Start(Tag::List(start)) => self.list(start),
+                Event::Start(Tag::CodeBlock(kind)) => self.code_block(kind),
+                Event::Start(Tag::HtmlBlock) => {
+                    ElementKind::Paragraph(self.inlines(TagEnd::HtmlBlock))
+                }
+                Event::Start(Tag::Table(alignments)) => self.table(alignments),
+                Event::Rule => ElementKind::Rule,
+                event => {
+                    self.inline(event, range, SpanAttributes::default(), &mut inlines);
+                    continue;
+                }
+            };
+
+            flush_paragraph(&mut elements, &mut inlines);
+            elements.push(Element::new(kind, range));
+        }
+
+        flush_paragraph(&mut elements, &mut inlines);
+        elements
+    }
+
+    fn list(&mut self, start: Option<u64>) -> ElementKind {
+        let mut items = Vec::new();
+
+        while let Some((event, range)) = self.events.next() {
+            match event {
+                Event::Start(Tag::Item) => {
+                    let outer = self.task.take();
+                    let children = self.blocks(Some(TagEnd::Item));
+                    let kind = match std::mem::replace(&mut self.task, outer) {
+                        Some(checked) => ElementKind::TaskItem { checked, children },
+                        None => ElementKind::Item(children),
+                    };
+                    items.push(Element::new(kind, range));
+                }
+                Event::End(TagEnd::List(_)) => break,
+                _ => {}
+            }
+        }
+
+        ElementKind::List {
+            ordered: start.is_some(),
+            start: start.unwrap_or(1),
+            items,
+        }
+    }
+
+    fn code_block(&mut self, kind: CodeBlockKind) -> ElementKind {
+        let lang = match kind {
+            CodeBlockKind::Fenced(info) => {
+                info.split_whitespace().next().map(|lang| lang.to_string())
+            }
+            CodeBlockKind::Indented => None,
+        };
+
+        let mut code = String::new();
+        for (event, _) in self.events.by_ref() {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => break,
+                _ => {}
+            }
+        }
+
+        ElementKind::CodeBlock { lang, code }
+    }
+
+    fn table(&mut self, alignments: Vec<pulldown_cmark::Alignment>) -> ElementKind {
+        let alignments = alignments
+            .into_iter()
+            .map(|alignment| match alignment {
+                pulldown_cmark::Alignment::None => Alignment::None,
+                pulldown_cmark::Alignment::Left => Alignment::Left,
+                pulldown_cmark::Alignment::Center => Alignment::Center,
+                pulldown_cmark::Alignment::Right => Alignment::Right,
+            })
+            .collect();
+
+        let mut header = Vec::new();
+        let mut rows = Vec::new();
+
+        while let Some((event, _)) = self.events.next() {
+            match event {
+                Event::Start(Tag::TableHead) => header = self.table_row(TagEnd::TableHead),
+                Event::Start(Tag::TableRow) => rows.push(self.table_row(TagEnd::TableRow)),
+                Event::End(TagEnd::Table) => break,
+                _ => {}
+            }
+        }
+
+        ElementKind::Table {
+            alignments,
+            header,
+            rows,
+        }
+    }
+
+    fn table_row(&mut self, end: TagEnd) -> Vec<TableCell> {
+        let mut cells = Vec::new();
+
+        while let Some((event, range)) = self.events.next() {
+            match event {
+                Event::Start(Tag::TableCell) => cells.push(TableCell {
+                    content: self.inlines(TagEnd::TableCell),
+                    range,
+                }),
+                Event::End(tag) if tag == end => break,
+                _ => {}
+            }
+        }
+
+        cells
+    }
+
+    /// Collects inline elements until `end` tag is reached
+    fn inlines(&mut self, end: TagEnd) -> Vec<Inline> {
+        let mut inlines = Vec::new();
+        self.inlines_into(end, SpanAttributes::default(), &mut inlines);
+        inlines
+    }
+
+    fn inlines_into(&mut self, end: TagEnd, attributes: SpanAttributes, inlines: &mut Vec<Inline>) {
+        while let Some((event, range)) = self.events.next() {
+            if event == Event::End(end) {
+                break;
+            }
+            self.inline(event, range, attributes, inlines);
+        }
+    }
+
+    fn inline(
+        &mut self,
+        event: Event,
+        range: Range<usize>,
+        attributes: SpanAttributes,
+        inlines: &mut Vec<Inline>,
+    ) {
+        match event {
+            Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                push_span(inlines, &text, attributes, range)
+            }
+            Event::Code(code) => push_span(
+                inlines,
+                &code,
+                SpanAttributes {
+                    monospace: true,
+                    ..attributes
+                },
+                range,
+            ),
+            Event::SoftBreak => push_span(inlines, " ", attributes, range),
+            Event::HardBreak => push_span(inlines, "\n", attributes, range),
+            Event::TaskListMarker(checked) => self.task = Some(checked),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                let mut content = Vec::new();
+                self.inlines_into(TagEnd::Link, attributes, &mut content);
+                let kind = InlineKind::Link {
+                    target: dest_url.to_string(),
+                    content,
+                };
+                inlines.push(Inline::new(kind, range));
+            }
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                let mut alt = String::new();
+                for (event, _) in self.events.by_ref() {
+                    match event {
+                        Event::Text(text) | Event::Code(text) => alt.push_str(&text),
+                        Event::End(TagEnd::Image) => break,
+                        _ => {}
+                    }
+                }
+                let kind = InlineKind::Image {
+                    src: dest_url.to_string(),
+                    alt,
+                };
+                inlines.push(Inline::new(kind, range));
+            }
+            Event::Start(tag) => {
+                let end = tag.to_end();
+                let mut attributes = attributes;
+                match tag {
+                    Tag::Emphasis => attributes.italic = true,
+                    Tag::Strong => attributes.bold = true,
+                    Tag::Strikethrough => attributes.strike = true,
+                    _ => {}
+                }
+                self.inlines_into(end, attributes, inlines);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn flush_paragraph(elements: &mut Vec<Element>, inlines: &mut Vec<Inline>) {
+    if let (Some(first), Some(last)) = (inlines.first(), inlines.last()) {
+        let range = first.range.start..last.range.end;
+        let kind = ElementKind::Paragraph(std::mem::take(inlines));
+        elements.push(Element::new(kind, range));
+    }
+}
+
+/// Appends text to the last span if it directly precedes given one
+/// and has the same attributes, otherwise starts a new span
+fn push_span(
+    inlines: &mut Vec<Inline>,
+    text: &str,
+    attributes: SpanAttributes,
+    range: Range<usize>,
+) {
+    if let Some(Inline {
+        kind: InlineKind::Span(last_text, last_attributes),
+        range: last_range,
+    }) = inlines.last_mut()
+    {
+        if *last_attributes == attributes && last_range.end == range.start {
+            last_text.push_str(text);
+            last_range.end = range.end;
+            return;
+        }
+    }
+
+    inlines.push(Inline::new(
+        InlineKind::Span(text.to_string(), attributes),
+        range,
+    ));
+}
+
+/// Heading in the document outline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineEntry {
+    pub level: u8,
+    pub title: String,
+    /// Source line the heading starts at
+    pub line: usize,
+}
+
+/// Lists headings of the document in order
+pub fn outline(text: &str) -> Vec<OutlineEntry> {
+    let mut entries = Vec::new();
+    // Frontmatter isn't part of the outline
+    let body = crate::frontmatter::span(text).map_or(0, |span| span.end);
+    let mut events = Parser::new_ext(&text[body..], options()).into_offset_iter();
+    // Offsets grow, so lines are counted from the previous heading
+    let (mut line, mut offset) = (0, 0);
+
+    while let Some((event, range)) = events.next() {
+        let Event::Start(Tag::Heading { level, .. }) = event else {
+            continue;
+        };
+
+        let mut title = String::new();
+        for (event, _) in events.by_ref() {
+            match event {
+                Event::Text(text) | Event::Code(text) => title.push_str(&text),
+                Event::SoftBreak | Event::HardBreak => title.push(' '),
+                Event::End(TagEnd::Heading(_)) => break,
+                _ => {}
+            }
+        }
+
+        let start = body + range.start;
+        line += text[offset..start].matches('\n').count();
+        offset = start;
+        entries.push(OutlineEntry {
+            level: level as u8,
+            title,
+            line,
+        });
+    }
+
+    entries
+}
+
+/// Wiki-style link like `[[note#heading|alias]]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    /// Name or relative path of the linked note
+    pub target: String,
+    pub heading: Option<String>,
+    pub alias: Option<String>,
+    /// Byte range of the link in the source text
+    pub range: Range<usize>,
+}
+
+/// Finds wiki links in the text, ignoring ones inside code
+pub fn wiki_links(text: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    let mut events = Parser::new_ext(text, options()).into_offset_iter();
+
+    while let Some((event, range)) = events.next() {
+        let Event::Start(Tag::Link {
+            link_type: LinkType::WikiLink { has_pothole },
+            dest_url,
+            ..
+        }) = event
+        else {
+            continue;
+        };
+
+        let mut label = String::new();
+        for (event, _) in events.by_ref() {
+            match event {
+                Event::Text(text) | Event::Code(text) => label.push_str(&text),
+                Event::End(TagEnd::Link) => break,
+                _ => {}
+            }
+        }
+
+        let (target, heading) = match dest_url.split_once('#') {
+            Some((target, heading)) => (target, Some(heading.trim().to_string())),
+            None => (dest_url.as_ref(), None),
+        };
+        links.push(WikiLink {
+            target: target.trim().to_string(),
+            heading,
+            alias: has_pothole.then_some(label),
+            range,
+        });
+    }
+
+    links
+}
+
+/// Task list item found in the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskItem {
+    /// Source line of the item
+    pub line: usize,
+    pub checked: bool,
+    /// Text of the item's first paragraph
+    pub text: String,
+}
+
+/// Lists task list items of the document in order
+pub fn tasks(text: &str) -> Vec<TaskItem> {
+    let mut tasks = Vec::new();
+    let mut events = Parser::new_ext(text, options()).into_offset_iter();
+    let (mut line, mut offset) = (0, 0);
+
+    while let Some((event, range)) = events.next() {
+        let Event::TaskListMarker(checked) = event else {
+            continue;
+        };
+
+        let mut label = String::new();
+        for (event, _) in events.by_ref() {
+            match event {
+                Event::Text(text) | Event::Code(text) => label.push_str(&text),
+                Event::SoftBreak | Event::HardBreak => label.push(' '),
+                Event::End(TagEnd::Paragraph | TagEnd::Item) | Event::Start(Tag::List(_)) => break,
+                _ => {}
+            }
+        }
+
+        line += text[offset..range.start].matches('\n').count();
+        offset = range.start;
+        tasks.push(TaskItem {
+            line,
+            checked,
+            text: label.trim().to_string(),
+        });
+    }
+
+    tasks
+}
+
+/// Finds the task marker of a list item line,
+/// returning byte offset of the character between brackets and whether it's checked
+pub fn task_marker(line: &str) -> Option<(usize, bool)> {
+    let content = line.trim_start_matches([' ', '\t']);
+    let marker_len = if content.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = content.chars().take_while(char::is_ascii_digit).count();
+        (digits > 0 && content[digits..].starts_with(['.', ')'])).then_some(digits + 1)?
+    };
+
+    let rest = &content[marker_len..];
+    let spaces = rest.len() - rest.trim_start_matches(' ').len();
+    let checkbox = rest[spaces..].get(..3)?;
+    let checked = match checkbox {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+
+    (spaces > 0).then_some((
+        line.len() - content.len() + marker_len + spaces + 1,
+        checked,
+    ))
+}
+
+/// Finds inline `#tags` in the text, ignoring ones inside code.
+/// Tags may be nested with `/`, like `#project/alpha`.
+pub fn tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut previous = None;
+
+    for event in Parser::new_ext(text, options()) {
+        let Event::Text(text) = event else {
+            previous = None;
+            continue;
+        };
+
+        let mut chars = text.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let starts_tag = c == '#' && previous.is_none_or(char::is_whitespace);
+            previous = Some(c);
+            if !starts_tag {
+                continue;
+            }
+
+            let rest = &text[i + 1..];
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '/')))
+                .unwrap_or(rest.len());
+            let tag = rest[..len].trim_end_matches('/');
+
+            // Tags need at least one non-digit, so `#1` stays an issue number
+            if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) {
+                tags.push(tag.to_string());
+            }
+            while chars.next_if(|(j, _)| *j <= i + len).is_some() {}
+            previous = Some('#');
+        }
+    }
+
+    tags
+}
+
+/// Marker line of a fenced code block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fence {
+    /// Either '`' or '~'
+    pub marker: char,
+    pub len: usize,
+}
+
+impl Fence {
+    /// Parses opening fence line, returning the fence and its info string
+    pub fn open(line: &str) -> Option<(Fence, &str)> {
+        let rest = strip_indent(line.trim_end_matches(['\n', '\r']))?;
+        let marker = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+        let len = rest.chars().take_while(|c| *c == marker).count();
+        let info = rest[len..].trim();
+
+        if len < 3 || (marker == '`' && info.contains('`')) {
+            return None;
+        }
+        Some((Fence { marker, len }, info))
+    }
+
+    /// Checks whether the line closes a block opened with this fence
+    pub fn closes(&self, line: &str) -> bool {
+        let Some(rest) = strip_indent(line.trim_end()) else {
+            return false;
+        };
+        let len = rest.chars().take_while(|c| *c == self.marker).count();
+        len >= self.len && len == rest.len()
+    }
+}
+
+/// Kind of markdown syntax within a source line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Markup {
+    Heading,
+    /// Syntax characters like `**`, backticks or link brackets
+    Marker,
+    Strong,
+    Emphasis,
+    Strikethrough,
+    Code,
+    Link,
+    ListMarker,
+    Quote,
+}
+
+/// Splits a single source line into ranges of markdown syntax.
+/// Constructs spanning several lines are not recognized.
+pub fn highlight_line(line: &str) -> Vec<(Range<usize>, Markup)> {
+    let content = line.trim_start_matches([' ', '\t']);
+    let indent = line.len() - content.len();
+
+    let mut paint: Vec<Option<Markup>> = vec![None; line.len()];
+    let mut fill = |range: Range<usize>, markup: Markup| {
+        for byte in &mut paint[range.start + indent..range.end + indent] {
+            *byte = Some(markup);
+        }
+    };
+
+    // Markup of each open tag, text takes the innermost one
+    let mut open: Vec<Option<Markup>> = Vec::new();
+    for (event, range) in Parser::new_ext(content, options()).into_offset_iter() {
+        match event {
+            Event::Start(tag) => {
+                let markup = match tag {
+                    Tag::Heading { .. } => {
+                        fill(range.clone(), Markup::Heading);
+                        Some(Markup::Heading)
+                    }
+                    Tag::BlockQuote(_) => {
+                        fill(range.clone(), Markup::Quote);
+                        if let Some(marker) = content[range.clone()].find('>') {
+                            let marker = range.start + marker;
+                            fill(marker..marker + 1, Markup::Marker);
+                        }
+                        None
+                    }
+                    Tag::Item => {
+                        let marker = content[range.clone()]
+                            .find(char::is_whitespace)
+                            .unwrap_or(range.len());
+                        fill(range.start..range.start + marker, Markup::ListMarker);
+                        None
+                    }
+                    Tag::Strong => Some(Markup::Strong),
+                    Tag::Emphasis => Some(Markup::Emphasis),
+                    Tag::Strikethrough => Some(Markup::Strikethrough),
+                    Tag::Link { .. } | Tag::Image { .. } => Some(Markup::Link),
+                    _ => None,
+                };
+                if matches!(
+                    markup,
+                    Some(Markup::Strong | Markup::Emphasis | Markup::Strikethrough | Markup::Link)
+                ) {
+                    fill(range, Markup::Marker);
+                }
+                open.push(markup);
+            }
+            Event::End(_) => {
+                open.pop();
+            }
+            Event::Text(_) => {
+                if let Some(markup) = open.iter().rev().flatten().next() {
+                    fill(range, *markup);
+                }
+            }
+            Event::Code(_) => {
+                fill(range.clone(), Markup::Marker);
+                let ticks = content[range.clone()]
+                    .chars()
+                    .take_while(|c| *c == '`')
+                    .count();
+                fill(range.start + ticks..range.end - ticks, Markup::Code);
+            }
+            Event::TaskListMarker(_) => fill(range, Markup::ListMarker),
+            Event::Rule => fill(range, Markup::Marker),
+            _ => {}
+        }
+    }
+
+    let mut ranges: Vec<(Range<usize>, Markup)> = Vec::new();
+    for (offset, markup) in paint.into_iter().enumerate() {
+        let Some(markup) = markup else {
+            continue;
+        };
+        match ranges.last_mut() {
+            Some((range, last)) if *last == markup && range.end == offset => range.end += 1,
+            _ => ranges.push((offset..offset + 1, markup)),
+        }
+    }
+    ranges
+}
+
+/// Strips up to three spaces of indentation allowed before block markers
+fn strip_indent(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches(' ');
+    (line.len() - rest.len() <= 3).then_some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(text: &str, range: Range<usize>) -> Inline {
+        Inline::new(
+            InlineKind::Span(text.to_string(), SpanAttributes::default()),
+            range,
+        )
+    }
+
+    fn paragraph(text: &str, span_range: Range<usize>, range: Range<usize>) -> Element {
+        Element::new(ElementKind::Paragraph(vec![span(text, span_range)]), range)
+    }
+
+    #[test]
+    fn task_items() {
+        let text = "- [ ] one\n  - [x] nested `code`\n\n1. [X] ordered\n";
+        let items: Vec<(usize, bool, String)> = tasks(text)
+            .into_iter()
+            .map(|item| (item.line, item.checked, item.text))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                (0, false, String::from("one")),
+                (1, true, String::from("nested code")),
+                (3, true, String::from("ordered")),
+            ]
+        );
+
+        assert_eq!(task_marker("  - [ ] task"), Some((5, false)));
+        assert_eq!(task_marker("12) [x] task"), Some((5, true)));
+        assert_eq!(task_marker("-[ ] task"), None);
+        assert_eq!(task_marker("- plain"), None);
+    }
+
+    #[test]
+    fn document_outline() {
+        let text =
+            "# Title\n\ntext\n\n## `Code` part\n\n```\n# not a heading\n```\n\nSetext\n---\n";
+        let entries: Vec<(u8, String, usize)> = outline(text)
+            .into_iter()
+            .map(|entry| (entry.level, entry.title, entry.line))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (1, String::from("Title"), 0),
+                (2, String::from("Code part"), 4),
+                (2, String::from("Setext"), 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_tags() {
+        let text = "#todo and #project/alpha, not a#b or #12\n\n`#code` #a_b-c/";
+        assert_eq!(tags(text), vec!["todo", "project/alpha", "a_b-c"]);
+    }
+
+    #[test]
+    fn wiki_link_parsing() {
+        let links = wiki_links("See [[Note]] and [[dir/Other#Part|alias]].\n\n`[[code]]`\n");
+        assert_eq!(
+            links,
+            vec![
+                WikiLink {
+                    target: String::from("Note"),
+                    heading: None,
+                    alias: None,
+                    range: 4..12,
+                },
+                WikiLink {
+                    target: String::from("dir/Other"),
+                    heading: Some(String::from("Part")),
+                    alias: Some(String::from("alias")),
+                    range: 17..41,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn headings_and_paragraphs() {
+        let elements = parse("# Title\n\nSome text\nwrapped\n\n### Sub");
+        assert_eq!(
+            elements,
+            vec![
+                Element::new(
+                    ElementKind::Heading {
+                        level: 1,
+                        content: vec![span("Title", 2..7)],
+                    },
+                    0..8
+                ),
+                paragraph("Some text wrapped", 9..26, 9..27),
+                Element::new(
+                    ElementKind::Heading {
+                        level: 3,
+                        content: vec![span("Sub", 32..35)],
+                    },
+                    28..35
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn frontmatter_skipped() {
+        let text = "---\ntitle: Spec\ntags: [a]\n---\n\n# Body";
+        let elements = parse(text);
+        assert_eq!(elements.len(), 1);
+        assert!(matches!(
+            elements[0].kind,
+            ElementKind::Heading { level: 1, .. }
+        ));
+        assert_eq!(&text[elements[0].range.clone()], "# Body");
+        assert!(parse("+++\ntitle = \"Spec\"\n+++\n").is_empty());
+        assert_eq!(
+            outline(text),
+            vec![OutlineEntry {
+                level: 1,
+                title: "Body".into(),
+                line: 5,
+            }]
+        );
+
+        // Inside a document `---` lines are rules and headings
+        assert_eq!(parse_fragment(text).len(), 3);
+    }
+
+    #[test]
+    fn inline_attributes() {
+        let elements = parse("a **b *c*** `d` ~~e~~");
+        let ElementKind::Paragraph(inlines) = &elements[0].kind else {
+            panic!("expected paragraph");
+        };
+
+        let spans: Vec<_> = inlines
+            .iter()
+            .map(|inline| match &inline.kind {
+                InlineKind::Span(text, attributes) => (text.as_str(), *attributes),
+                kind => panic!("unexpected inline {kind:?}"),
+            })
+            .collect();
+
+        let bold = SpanAttributes {
+            bold: true,
+            ..Default::default()
+        };
+        assert_eq!(spans[0], ("a ", SpanAttributes::default()));
+        assert_eq!(spans[1], ("b ", bold));
+        assert_eq!(
+            spans[2],
+            (
+                "c",
+                SpanAttributes {
+                    italic: true,
+                    ..bold
+                }
+            )
+        );
+        assert_eq!(
+            spans[4],
+            (
+                "d",
+                SpanAttributes {
+                    monospace: true,
+                    ..Default::default()
+                }
+            )
+        );
+        assert_eq!(
+            spans[6],
+            (
+                "e",
+                SpanAttributes {
+                    strike: true,
+                    ..Default::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn links_and_images() {
+        let elements = parse("[a *b*](http://c) ![alt](img.png)");
+        let ElementKind::Paragraph(inlines) = &elements[0].kind else {
+            panic!("expected paragraph");
+        };
+
+        let InlineKind::Link { target, content } = &inlines[0].kind else {
+            panic!("expected link");
+        };
+        assert_eq!(target, "http://c");
+        assert_eq!(inlines[0].range, 0..17);
+        assert_eq!(content.len(), 2);
+
+        assert_eq!(
+            inlines[2],
+            Inline::new(
+                InlineKind::Image {
+                    src: "img.png".to_string(),
+                    alt: "alt".to_string(),
+                },
+                18..33
+            )
+        );
+    }
+
+    #[test]
+    fn lists() {
+        let elements = parse("- [ ] one\n- [x] two\n  1. nested\n\n3. three");
+        assert_eq!(elements.len(), 2);
+
+        let ElementKind::List {
+            ordered,
+            start,
+            items,
+        } = &elements[0].kind
+        else {
+            panic!("expected list");
+        };
+        assert!(!ordered);
+        assert_eq!(*start, 1);
+        assert_eq!(
+            items[0],
+            Element::new(
+                ElementKind::TaskItem {
+                    checked: false,
+                    children: vec![paragraph("one", 6..9, 6..9)],
+                },
+                0..10
+            )
+        );
+
+        let ElementKind::TaskItem { checked, children } = &items[1].kind else {
+            panic!("expected task item");
+        };
+        assert!(checked);
+        assert_eq!(
+            children[1].kind,
+            ElementKind::List {
+                ordered: true,
+                start: 1,
+                items: vec![Element::new(
+                    ElementKind::Item(vec![paragraph("nested", 25..31, 25..31)]),
+                    22..33
+                )],
+            }
+        );
+
+        assert!(matches!(
+            elements[1].kind,
+            ElementKind::List {
+                ordered: true,
+                start: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn block_elements() {
+        let elements = parse("> quote\n\n```rust extra\nfn main() {}\n```\n\n---\n\n    indented");
+        let kinds: Vec<_> = elements.into_iter().map(|element| element.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ElementKind::BlockQuote(vec![paragraph("quote", 2..7, 2..8)]),
+                ElementKind::CodeBlock {
+                    lang: Some("rust".to_string()),
+                    code: "fn main() {}\n".to_string(),
+                },
+                ElementKind::Rule,
+                ElementKind::CodeBlock {
+                    lang: None,
+                    code: "indented".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tables() {
+        let elements = parse("| a | b |\n|:--|--:|\n| 1 | 2 |\n");
+        let ElementKind::Table {
+            alignments,
+            header,
+            rows,
+        } = &elements[0].kind
+        else {
+            panic!("expected table");
+        };
+        assert_eq!(alignments, &vec![Alignment::Left, Alignment::Right]);
+        assert_eq!(header[0].content, vec![span("a", 2..3)]);
+        assert_eq!(rows[0][1].content, vec![span("2", 26..27)]);
+    }
+
+    #[test]
+    fn element_at_offset() {
+        let buffer = crate::buffer::Buffer::new("# A\n\n- one\n- two\n");
+        let formatted = crate::buffer::FormattedBuffer::from_buffer(&buffer);
+
+        assert!(matches!(
+            formatted.element_at(1).map(|element| &element.kind),
+            Some(ElementKind::Heading { .. })
+        ));
+        assert_eq!(
+            formatted
+                .element_at(13)
+                .map(|element| element.range.clone()),
+            Some(13..16)
+        );
+    }
+
+    #[test]
+    fn fences() {
+        let (fence, info) = Fence::open("```rust ignore\n").unwrap();
+        assert_eq!(
+            fence,
+            Fence {
+                marker: '`',
+                len: 3
+            }
+        );
+        assert_eq!(info, "rust ignore");
+
+        assert_eq!(Fence::open("  ~~~~").unwrap().0.len, 4);
+        assert!(Fence::open("``").is_none());
+        assert!(Fence::open("    ```").is_none());
+        assert!(Fence::open("``` a`b").is_none());
+
+        assert!(fence.closes("```\n"));
+        assert!(fence.closes("   `````  "));
+        assert!(!fence.closes("~~~"));
+        assert!(!fence.closes("``"));
+        assert!(!fence.closes("``` rust"));
+    }
+
+    #[test]
+    fn line_highlighting() {
+        assert_eq!(
+            highlight_line("## Title *it*"),
+            vec![
+                (0..9, Markup::Heading),
+                (9..10, Markup::Marker),
+                (10..12, Markup::Emphasis),
+                (12..13, Markup::Marker),
+            ]
+        );
+
+        assert_eq!(
+            highlight_line("a **b** `c`"),
+            vec![
+                (2..4, Markup::Marker),
+                (4..5, Markup::Strong),
+                (5..7, Markup::Marker),
+                (8..9, Markup::Marker),
+                (9..10, Markup::Code),
+                (10..11, Markup::Marker),
+            ]
+        );
+
+        assert_eq!(
+            highlight_line("  - [x] [l](u)"),
+            vec![
+                (2..3, Markup::ListMarker),
+                (4..7, Markup::ListMarker),
+                (8..9, Markup::Marker),
+                (9..10, Markup::Link),
+                (10..14, Markup::Marker),
+            ]
+        );
+
+        assert_eq!(
+            highlight_line("> quote"),
+            vec![(0..1, Markup::Marker), (1..7, Markup::Quote)]
+        );
+        assert!(highlight_line("plain text").is_empty());
+    }
+}
@@ -1,6 +1,9 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+};
 
-use crate::document::DocumentId;
+use crate::{document::DocumentId, encoding::TextFormat};
 
 pub type PaneId = usize;
 
@@ -12,49 +15,226 @@ pub enum Pane {
     Editor(DocumentId),
     Buffer,
     Config,
+    /// Notes linking to the document
+    Backlinks(DocumentId),
+    /// Rendered Markdown of the document
+    Preview(DocumentId),
+    /// Tags of the workdir notes
+    Tags,
+    /// Open tasks of the workdir notes
+    Tasks,
+    /// Unsaved documents left by a previous run
+    Recovery,
 }
 
-#[derive(Default, Debug)]
+pub type GroupId = usize;
+
+/// Share of a split any of its sides is kept above
+const MIN_RATIO: f32 = 0.1;
+
+/// Number of closed panes kept to be reopened
+const CLOSED_LIMIT: usize = 20;
+
+/// Axis the sides of a split are placed along
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Side by side
+    Row,
+    /// One above the other
+    Column,
+}
+
+/// Tree of splits with a tab group in every leaf
+#[derive(Debug, Clone, PartialEq)]
+pub enum Layout {
+    Group(GroupId),
+    Split {
+        axis: Axis,
+        /// Share of the first side
+        ratio: f32,
+        first: Box<Layout>,
+        second: Box<Layout>,
+    },
+}
+
+impl Layout {
+    /// Ids of tab groups from the top left to the bottom right
+    pub fn groups(&self) -> Vec<GroupId> {
+        match self {
+            Layout::Group(id) => vec![*id],
+            Layout::Split { first, second, .. } => {
+                let mut groups = first.groups();
+                groups.extend(second.groups());
+                groups
+            }
+        }
+    }
+
+    fn contains(&self, group: GroupId) -> bool {
+        match self {
+            Layout::Group(id) => *id == group,
+            Layout::Split { first, second, .. } => first.contains(group) || second.contains(group),
+        }
+    }
+
+    fn split(&mut self, group: GroupId, new: GroupId, axis: Axis) {
+        match self {
+            Layout::Group(id) if *id == group => {
+                *self = Layout::Split {
+                    axis,
+                    ratio: 0.5,
+                    first: Box::new(Layout::Group(group)),
+                    second: Box::new(Layout::Group(new)),
+                };
+            }
+            Layout::Group(_) => {}
+            Layout::Split { first, second, .. } => {
+                first.split(group, new, axis);
+                second.split(group, new, axis);
+            }
+        }
+    }
+
+    /// Removes the group, its sibling takes the place of their split
+    fn remove(&mut self, group: GroupId) {
+        if let Layout::Split { first, second, .. } = self {
+            if **first == Layout::Group(group) {
+                *self = std::mem::replace(second, Layout::Group(group));
+            } else if **second == Layout::Group(group) {
+                *self = std::mem::replace(first, Layout::Group(group));
+            } else {
+                first.remove(group);
+                second.remove(group);
+            }
+        }
+    }
+
+    /// Changes ratio of the innermost split holding the group.
+    /// Returns false if no split holds it.
+    fn resize(&mut self, group: GroupId, delta: f32) -> bool {
+        let Layout::Split {
+            ratio,
+            first,
+            second,
+            ..
+        } = self
+        else {
+            return false;
+        };
+
+        let delta = if first.contains(group) {
+            if first.resize(group, delta) {
+                return true;
+            }
+            delta
+        } else if second.contains(group) {
+            if second.resize(group, delta) {
+                return true;
+            }
+            -delta
+        } else {
+            return false;
+        };
+
+        *ratio = (*ratio + delta).clamp(MIN_RATIO, 1.0 - MIN_RATIO);
+        true
+    }
+}
+
+/// Tabs of one split
+#[derive(Debug, Default, Clone)]
+pub struct TabGroup {
+    tabs: Vec<PaneId>,
+    open: Option<PaneId>,
+}
+
+impl TabGroup {
+    pub fn tabs(&self) -> &[PaneId] {
+        &self.tabs
+    }
+
+    pub fn open(&self) -> Option<PaneId> {
+        self.open
+    }
+}
+
+/// Panes arranged into tab groups of a split layout.
+/// Adding, opening and reading the open pane work with the focused group.
+#[derive(Debug)]
 pub struct PaneModel {
     panes: HashMap<PaneId, Pane>,
-    visible: Vec<PaneId>,
+    groups: HashMap<GroupId, TabGroup>,
+    layout: Layout,
+    focused: GroupId,
     next_id: PaneId,
-    open: Option<PaneId>,
+    next_group: GroupId,
+}
+
+impl Default for PaneModel {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PaneModel {
     pub fn new() -> Self {
         Self {
             panes: HashMap::new(),
-            visible: Vec::new(),
+            groups: HashMap::from([(1, TabGroup::default())]),
+            layout: Layout::Group(1),
+            focused: 1,
             next_id: 1,
-            open: None,
+            next_group: 2,
+        }
+    }
+
+    /// Adds the pane at the index among tabs of the group.
+    /// The pane goes to the focused group if that group was closed.
+    pub fn insert(&mut self, pane: Pane, group: &GroupId, index: usize) -> PaneId {
+        let id = self.next_id;
+        let group = if self.groups.contains_key(group) {
+            *group
+        } else {
+            self.focused
+        };
+
+        self.panes.insert(id, pane);
+        if let Some(group) = self.groups.get_mut(&group) {
+            let index = index.min(group.tabs.len());
+            group.tabs.insert(index, id);
         }
+        self.next_id += 1;
+
+        id
     }
 
     pub fn add(&mut self, pane: Pane) -> PaneId {
         let id = self.next_id;
 
         self.panes.insert(id, pane);
-        self.visible.push(id);
+        if let Some(group) = self.groups.get_mut(&self.focused) {
+            group.tabs.push(id);
+        }
         self.next_id += 1;
 
         id
     }
 
+    /// Removes the pane, closing its split if it was the last pane there
     pub fn remove(&mut self, id: &PaneId) -> Option<Pane> {
-        // If removing pane is opened, close them
-        if self.open == Some(*id) {
-            // if there are more panels, open the last one
-            if let Some(last) = self.visible.last() {
-                self.open = Some(*last);
-            } else {
-                // Otherwise leave it not opened
-                self.open = None;
+        if let Some(group_id) = self.group_of(id) {
+            if let Some(group) = self.groups.get_mut(&group_id) {
+                group.tabs.retain(|&x| x != *id);
+                // If removing pane is opened, open the last one left
+                if group.open == Some(*id) {
+                    group.open = group.tabs.last().copied();
+                }
+                if group.tabs.is_empty() && self.groups.len() > 1 {
+                    self.close_group(&group_id);
+                }
             }
         }
 
-        self.visible.retain(|&x| x != *id);
         self.panes.remove(id)
     }
 
@@ -74,26 +254,36 @@ impl PaneModel {
         self.panes.get_mut(id)
     }
 
+    /// Panes of all groups in the layout order
     pub fn list(&self) -> Vec<(&PaneId, &Pane)> {
-        self.visible
+        self.layout
+            .groups()
             .iter()
+            .filter_map(|group| self.groups.get(group))
+            .flat_map(|group| group.tabs.iter())
             .filter_map(|id| self.panes.get(id).map(|pane| (id, pane)))
             .collect()
     }
 
+    /// Opens the pane in its group and focuses the group
     pub fn open(&mut self, id: &PaneId) {
-        if self.panes.contains_key(id) && self.open != Some(*id) {
-            self.open = Some(*id);
+        if let Some(group_id) = self.group_of(id) {
+            if let Some(group) = self.groups.get_mut(&group_id) {
+                group.open = Some(*id);
+            }
+            self.focused = group_id;
         }
     }
 
     pub fn get_open_id(&self) -> Option<&PaneId> {
-        self.open.as_ref()
+        self.groups
+            .get(&self.focused)
+            .and_then(|group| group.open.as_ref())
     }
 
     pub fn get_open(&self) -> Option<&Pane> {
-        if let Some(id) = self.open {
-            self.panes.get(&id)
+        if let Some(id) = self.get_open_id() {
+            self.panes.get(id)
         } else {
             None
         }
@@ -104,4 +294,301 @@ impl PaneModel {
             *pane = new;
         }
     }
+
+    /// Moves the pane to the index among tabs of its group, clamped to the last tab
+    pub fn move_pane(&mut self, id: &PaneId, index: usize) {
+        let Some(group) = self
+            .group_of(id)
+            .and_then(|group| self.groups.get_mut(&group))
+        else {
+            return;
+        };
+
+        group.tabs.retain(|&x| x != *id);
+        let index = index.min(group.tabs.len());
+        group.tabs.insert(index, *id);
+    }
+
+    /// Moves the pane to the end of another group, opening it there.
+    /// The old group is closed if the pane was its last one.
+    pub fn move_to_group(&mut self, id: &PaneId, target: &GroupId) {
+        let Some(source) = self.group_of(id) else {
+            return;
+        };
+        if source == *target || !self.groups.contains_key(target) {
+            return;
+        }
+
+        if let Some(group) = self.groups.get_mut(&source) {
+            group.tabs.retain(|&x| x != *id);
+            if group.open == Some(*id) {
+                group.open = group.tabs.last().copied();
+            }
+            if group.tabs.is_empty() {
+                self.close_group(&source);
+            }
+        }
+        if let Some(group) = self.groups.get_mut(target) {
+            group.tabs.push(*id);
+        }
+        self.open(id);
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    pub fn group(&self, id: &GroupId) -> Option<&TabGroup> {
+        self.groups.get(id)
+    }
+
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Group holding the pane and index of the pane among its tabs
+    pub fn position(&self, id: &PaneId) -> Option<(GroupId, usize)> {
+        let group = self.group_of(id)?;
+        let index = self.groups.get(&group)?.tabs.iter().position(|x| x == id)?;
+        Some((group, index))
+    }
+
+    /// Group holding the pane
+    pub fn group_of(&self, id: &PaneId) -> Option<GroupId> {
+        self.groups
+            .iter()
+            .find(|(_, group)| group.tabs.contains(id))
+            .map(|(group_id, _)| *group_id)
+    }
+
+    pub fn focused(&self) -> GroupId {
+        self.focused
+    }
+
+    pub fn focus(&mut self, id: &GroupId) {
+        if self.groups.contains_key(id) {
+            self.focused = *id;
+        }
+    }
+
+    /// Focuses the next group in the layout order, wrapping around
+    pub fn focus_next(&mut self) {
+        let groups = self.layout.groups();
+        if let Some(i) = groups.iter().position(|&id| id == self.focused) {
+            self.focused = groups[(i + 1) % groups.len()];
+        }
+    }
+
+    /// Focuses the previous group in the layout order, wrapping around
+    pub fn focus_previous(&mut self) {
+        let groups = self.layout.groups();
+        if let Some(i) = groups.iter().position(|&id| id == self.focused) {
+            self.focused = groups[(i + groups.len() - 1) % groups.len()];
+        }
+    }
+
+    /// Splits the focused group, the new group shows the open pane of the focused one.
+    /// Returns id of the new group, which gets focused.
+    pub fn split(&mut self, axis: Axis) -> GroupId {
+        let id = self.next_group;
+        self.next_group += 1;
+
+        self.layout.split(self.focused, id, axis);
+        self.groups.insert(id, TabGroup::default());
+
+        let pane = self.get_open().copied().unwrap_or(Pane::NewDocument);
+        self.focused = id;
+        let pane = self.add(pane);
+        self.open(&pane);
+
+        id
+    }
+
+    /// Removes the group with its panes, unless it's the only one.
+    /// Returns the removed panes.
+    pub fn close_group(&mut self, id: &GroupId) -> Vec<(PaneId, Pane)> {
+        if self.groups.len() < 2 {
+            return Vec::new();
+        }
+        let Some(group) = self.groups.remove(id) else {
+            return Vec::new();
+        };
+
+        self.layout.remove(*id);
+        if self.focused == *id {
+            self.focused = self.layout.groups().first().copied().unwrap_or_default();
+        }
+
+        group
+            .tabs
+            .into_iter()
+            .filter_map(|pane| self.panes.remove(&pane).map(|removed| (pane, removed)))
+            .collect()
+    }
+
+    /// Grows the focused group by the share of its split, shrinking it with a negative one
+    pub fn resize(&mut self, delta: f32) {
+        self.layout.resize(self.focused, delta);
+    }
+}
+
+/// Pane closed by the user, kept to be reopened
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosedPane {
+    /// Pane as it was, its document may be closed by now
+    pub pane: Pane,
+    pub group: GroupId,
+    /// Index among tabs of the group
+    pub index: usize,
+    /// Document of an editor pane
+    pub document: Option<ClosedDocument>,
+}
+
+/// Document of a closed editor pane
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosedDocument {
+    pub path: Option<PathBuf>,
+    pub filename: String,
+    /// Text with edits that weren't saved when the pane was closed
+    pub unsaved: Option<String>,
+    pub format: TextFormat,
+    /// Byte offset of the cursor
+    pub cursor: usize,
+    /// First line shown in the editor
+    pub scroll: usize,
+}
+
+/// Recently closed panes, the oldest ones are forgotten first
+#[derive(Debug, Default)]
+pub struct ClosedPanes {
+    panes: VecDeque<ClosedPane>,
+}
+
+impl ClosedPanes {
+    pub fn push(&mut self, pane: ClosedPane) {
+        if self.panes.len() == CLOSED_LIMIT {
+            self.panes.pop_front();
+        }
+        self.panes.push_back(pane);
+    }
+
+    /// Takes the most recently closed pane
+    pub fn pop(&mut self) -> Option<ClosedPane> {
+        self.panes.pop_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.panes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.panes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_layout() {
+        let mut panes = PaneModel::new();
+        let first = panes.focused();
+        let editor = panes.add(Pane::Editor(1));
+        panes.open(&editor);
+
+        let second = panes.split(Axis::Row);
+        assert_eq!(panes.get_open(), Some(&Pane::Editor(1)));
+        let third = panes.split(Axis::Column);
+        assert_eq!(panes.layout().groups(), vec![first, second, third]);
+        assert_eq!(panes.list().len(), 3);
+
+        panes.resize(0.25);
+        panes.focus_next();
+        assert_eq!(panes.focused(), first);
+        panes.resize(1.0);
+        assert_eq!(
+            panes.layout(),
+            &Layout::Split {
+                axis: Axis::Row,
+                ratio: 0.9,
+                first: Box::new(Layout::Group(first)),
+                second: Box::new(Layout::Split {
+                    axis: Axis::Column,
+                    ratio: 0.25,
+                    first: Box::new(Layout::Group(second)),
+                    second: Box::new(Layout::Group(third)),
+                }),
+            }
+        );
+
+        // Closing the last tab closes the split
+        panes.focus(&second);
+        let open = *panes.get_open_id().unwrap();
+        panes.remove(&open);
+        assert_eq!(panes.layout().groups(), vec![first, third]);
+        assert_eq!(panes.focused(), first);
+
+        assert_eq!(panes.close_group(&third).len(), 1);
+        assert_eq!(panes.layout(), &Layout::Group(first));
+        assert!(panes.close_group(&first).is_empty());
+    }
+
+    #[test]
+    fn reorder_tabs() {
+        let mut panes = PaneModel::new();
+        let first = panes.focused();
+        let [a, b, c] = [Pane::Tags, Pane::Tasks, Pane::Config].map(|pane| panes.add(pane));
+        let tabs = |panes: &PaneModel, group| panes.group(&group).unwrap().tabs().to_vec();
+
+        panes.move_pane(&a, 2);
+        assert_eq!(tabs(&panes, first), vec![b, c, a]);
+        panes.move_pane(&c, 0);
+        assert_eq!(tabs(&panes, first), vec![c, b, a]);
+        panes.move_pane(&c, 10);
+        assert_eq!(tabs(&panes, first), vec![b, a, c]);
+
+        panes.open(&b);
+        let second = panes.split(Axis::Row);
+        let copy = *panes.get_open_id().unwrap();
+        panes.move_to_group(&a, &second);
+        assert_eq!(tabs(&panes, first), vec![b, c]);
+        assert_eq!(tabs(&panes, second), vec![copy, a]);
+        assert_eq!(panes.get_open_id(), Some(&a));
+
+        // Moving the only tab away closes its split
+        panes.move_to_group(&a, &first);
+        panes.move_to_group(&copy, &first);
+        assert_eq!(panes.layout(), &Layout::Group(first));
+        assert_eq!(tabs(&panes, first), vec![b, c, a, copy]);
+        assert_eq!(panes.focused(), first);
+    }
+
+    #[test]
+    fn reopen_closed() {
+        let mut panes = PaneModel::new();
+        let group = panes.focused();
+        let [tags, tasks] = [Pane::Tags, Pane::Tasks].map(|pane| panes.add(pane));
+
+        let mut closed = ClosedPanes::default();
+        for i in 0..CLOSED_LIMIT + 5 {
+            closed.push(ClosedPane {
+                pane: Pane::Config,
+                group,
+                index: i,
+                document: None,
+            });
+        }
+        assert_eq!(closed.len(), CLOSED_LIMIT);
+
+        let last = closed.pop().unwrap();
+        assert_eq!(last.index, CLOSED_LIMIT + 4);
+        let config = panes.insert(last.pane, &last.group, 1);
+        assert_eq!(panes.position(&config), Some((group, 1)));
+        assert_eq!(panes.group(&group).unwrap().tabs(), [tags, config, tasks]);
+
+        // Closed group falls back to the focused one
+        let moved = panes.insert(Pane::Recovery, &(group + 10), 0);
+        assert_eq!(panes.position(&moved), Some((group, 0)));
+    }
 }
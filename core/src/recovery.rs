@@ -0,0 +1,190 @@
+//! Journal of unsaved document text for recovery after a crash
+
+use std::{fs, io, path::PathBuf};
+
+use crate::{
+    diff::{self, Diff},
+    encoding::{self, TextFormat},
+};
+
+const EXTENSION: &str = "swap";
+
+/// Marks keys of untitled documents in swap files, paths never start with it
+const UNTITLED: &str = "untitled:";
+
+/// Document the journaled text belongs to
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Key {
+    File(PathBuf),
+    /// Untitled document by a key unique among runs of the app
+    Untitled(String),
+}
+
+impl Key {
+    fn to_line(&self) -> String {
+        match self {
+            Key::File(path) => path.to_string_lossy().into_owned(),
+            Key::Untitled(key) => format!("{UNTITLED}{key}"),
+        }
+    }
+
+    fn from_line(line: &str) -> Self {
+        match line.strip_prefix(UNTITLED) {
+            Some(key) => Key::Untitled(key.to_string()),
+            None => Key::File(PathBuf::from(line)),
+        }
+    }
+}
+
+/// Unsaved text of a document found in the journal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub key: Key,
+    /// Journaled text
+    pub text: String,
+    /// Current text of the file on disk, if it's readable
+    pub saved: Option<String>,
+    /// Format of the file on disk
+    pub format: TextFormat,
+    /// Changes of the journaled text against the saved one, all of it is new
+    /// for untitled documents
+    pub diff: Option<Diff>,
+}
+
+/// Directory with one swap file per document.
+/// Swap file holds the document key on the first line and its text after it.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    directory: PathBuf,
+}
+
+impl Journal {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Stores text of the document, replacing the previous one
+    pub fn write(&self, key: &Key, text: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+
+        let line = key.to_line();
+        let swap = self.swap_path(&line);
+        let temp = swap.with_extension("tmp");
+        fs::write(&temp, format!("{line}\n{text}"))?;
+        fs::rename(temp, swap)
+    }
+
+    /// Forgets the document, usually after it was saved
+    pub fn remove(&self, key: &Key) -> io::Result<()> {
+        match fs::remove_file(self.swap_path(&key.to_line())) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads journaled documents, files sorted by path before untitled documents.
+    /// Texts that are already saved in their files are forgotten.
+    pub fn entries(&self) -> Vec<Entry> {
+        let Ok(files) = fs::read_dir(&self.directory) else {
+            return Vec::new();
+        };
+
+        let mut entries: Vec<Entry> = files
+            .flatten()
+            .map(|file| file.path())
+            .filter(|file| file.extension().is_some_and(|ext| ext == EXTENSION))
+            .filter_map(|file| {
+                let swap = fs::read_to_string(&file).ok()?;
+                let (line, text) = swap.split_once('\n')?;
+                let key = Key::from_line(line);
+                let (saved, format) = match &key {
+                    Key::File(path) => match fs::read(path) {
+                        Ok(bytes) => {
+                            let (saved, format) = encoding::decode(&bytes);
+                            (Some(saved), format)
+                        }
+                        Err(_) => (None, TextFormat::default()),
+                    },
+                    Key::Untitled(_) => (None, TextFormat::default()),
+                };
+                // Editor text has no CRLF endings, even of mixed files
+                if saved
+                    .as_ref()
+                    .is_some_and(|saved| saved.replace("\r\n", "\n") == text)
+                {
+                    // Failing to remove it only costs another check on the next start
+                    fs::remove_file(&file).ok();
+                    return None;
+                }
+                let diff = match (&key, &saved) {
+                    (Key::Untitled(_), _) => Some(diff::lines("", text)),
+                    (Key::File(_), saved) => saved.as_deref().map(|saved| diff::lines(saved, text)),
+                };
+                Some(Entry {
+                    diff,
+                    key,
+                    text: text.to_string(),
+                    saved,
+                    format,
+                })
+            })
+            .collect();
+
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        entries
+    }
+
+    fn swap_path(&self, line: &str) -> PathBuf {
+        // FNV-1a, stable between runs unlike the std hasher
+        let hash = line.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        self.directory.join(format!("{hash:016x}.{EXTENSION}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal() {
+        let directory =
+            std::env::temp_dir().join(format!("strelka-journal-{}", std::process::id()));
+        let journal = Journal::new(&directory);
+        let note = directory.join("note.md");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&note, "saved\n").unwrap();
+
+        let note = Key::File(note);
+        let untitled = Key::Untitled(String::from("1-2"));
+
+        journal.write(&note, "first\n").unwrap();
+        journal.write(&note, "unsaved\nedit\n").unwrap();
+        journal
+            .write(&Key::File(PathBuf::from("/missing.md")), "")
+            .unwrap();
+        journal.write(&untitled, "draft\n").unwrap();
+        let saved = directory.join("saved.md");
+        fs::write(&saved, "same\r\n").unwrap();
+        journal.write(&Key::File(saved), "same\n").unwrap();
+
+        let entries = journal.entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].key, Key::File(PathBuf::from("/missing.md")));
+        assert_eq!(entries[0].saved, None);
+        assert_eq!(entries[1].text, "unsaved\nedit\n");
+        assert_eq!(entries[1].saved.as_deref(), Some("saved\n"));
+        assert_eq!(entries[2].key, untitled);
+        assert_eq!(entries[2].text, "draft\n");
+
+        journal.remove(&note).unwrap();
+        journal.remove(&note).unwrap();
+        journal.remove(&untitled).unwrap();
+        assert_eq!(journal.entries().len(), 1);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+//! Text search with literal, whole word and regex modes
+
+use std::ops::Range;
+
+use regex::{NoExpand, Regex, RegexBuilder};
+
+/// Search pattern with matching options
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub pattern: String,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// Treat pattern as a regular expression
+    pub regex: bool,
+}
+
+impl SearchQuery {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn case_sensitive(mut self, value: bool) -> Self {
+        self.case_sensitive = value;
+        self
+    }
+
+    pub fn whole_word(mut self, value: bool) -> Self {
+        self.whole_word = value;
+        self
+    }
+
+    pub fn regex(mut self, value: bool) -> Self {
+        self.regex = value;
+        self
+    }
+
+    /// Compiles the query, failing on invalid regex pattern
+    pub fn build(&self) -> Result<Searcher, String> {
+        let pattern = if self.regex {
+            self.pattern.clone()
+        } else {
+            regex::escape(&self.pattern)
+        };
+        let pattern = if self.whole_word {
+            format!(r"\b(?:{pattern})\b")
+        } else {
+            pattern
+        };
+
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .multi_line(true)
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        Ok(Searcher {
+            regex,
+            expand: self.regex,
+            empty: self.pattern.is_empty(),
+        })
+    }
+}
+
+/// Compiled search query
+#[derive(Debug, Clone)]
+pub struct Searcher {
+    regex: Regex,
+    /// Expand capture group references like `$1` in replacements
+    expand: bool,
+    empty: bool,
+}
+
+impl Searcher {
+    /// Returns byte ranges of all non-empty matches in the text
+    pub fn find_all(&self, text: &str) -> Vec<Range<usize>> {
+        if self.empty {
+            return Vec::new();
+        }
+
+        self.regex
+            .find_iter(text)
+            .map(|found| found.range())
+            .filter(|range| !range.is_empty())
+            .collect()
+    }
+
+    /// Returns replacement text for the match at given range
+    pub fn replacement(&self, text: &str, range: Range<usize>, replacement: &str) -> String {
+        if !self.expand {
+            return replacement.to_string();
+        }
+
+        let mut result = String::new();
+        match self.regex.captures_at(text, range.start) {
+            Some(captures) if captures.get(0).map(|m| m.range()) == Some(range) => {
+                captures.expand(replacement, &mut result)
+            }
+            _ => result.push_str(replacement),
+        }
+        result
+    }
+
+    /// Replaces all matches in the text, returning new text and number of replacements
+    pub fn replace_all(&self, text: &str, replacement: &str) -> (String, usize) {
+        let count = self.find_all(text).len();
+        if count == 0 {
+            return (text.to_string(), 0);
+        }
+
+        let replaced = if self.expand {
+            self.regex.replace_all(text, replacement)
+        } else {
+            self.regex.replace_all(text, NoExpand(replacement))
+        };
+        (replaced.into_owned(), count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(query: SearchQuery, text: &str) -> Vec<Range<usize>> {
+        query.build().unwrap().find_all(text)
+    }
+
+    #[test]
+    fn literal() {
+        let text = "Cat cat concat (cat)";
+        assert_eq!(
+            find(SearchQuery::new("cat"), text),
+            vec![0..3, 4..7, 11..14, 16..19]
+        );
+        assert_eq!(
+            find(SearchQuery::new("cat").case_sensitive(true), text),
+            vec![4..7, 11..14, 16..19]
+        );
+        assert_eq!(find(SearchQuery::new("(cat)"), text), vec![15..20]);
+        assert!(find(SearchQuery::new(""), text).is_empty());
+    }
+
+    #[test]
+    fn whole_word() {
+        let text = "cat concat cat_s cat.";
+        assert_eq!(
+            find(SearchQuery::new("cat").whole_word(true), text),
+            vec![0..3, 17..20]
+        );
+    }
+
+    #[test]
+    fn regex() {
+        let text = "v1 v22 x3\nv4";
+        let query = SearchQuery::new(r"v(\d+)").regex(true);
+        assert_eq!(find(query.clone(), text), vec![0..2, 3..6, 10..12]);
+        assert_eq!(
+            find(SearchQuery::new("^v").regex(true), text),
+            vec![0..1, 10..11]
+        );
+        assert!(find(SearchQuery::new("x*").regex(true), "abc").is_empty());
+
+        let searcher = query.build().unwrap();
+        assert_eq!(searcher.replacement(text, 3..6, "n$1"), "n22");
+        assert_eq!(
+            searcher.replace_all(text, "n$1"),
+            ("n1 n22 x3\nn4".to_string(), 3)
+        );
+
+        assert!(SearchQuery::new("(").regex(true).build().is_err());
+    }
+
+    #[test]
+    fn literal_replacement() {
+        let searcher = SearchQuery::new("a").build().unwrap();
+        assert_eq!(searcher.replacement("a", 0..1, "$0"), "$0");
+        assert_eq!(
+            searcher.replace_all("a b A", "$0"),
+            ("$0 b $0".to_string(), 2)
+        );
+        assert_eq!(searcher.replace_all("b", "c"), ("b".to_string(), 0));
+    }
+}
@@ -13,6 +13,7 @@ pub enum Value {
     Color(Color),
     Path(PathBuf),
     String(SmolStr),
+    List(Vec<Value>),
 }
 
 #[cfg(feature = "serde")]
@@ -27,13 +28,22 @@ impl<'de> serde::Deserialize<'de> for Value {
             type Value = Value;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("an integer, float, boolean, string, or color")
+                formatter.write_str("an integer, float, boolean, string, color or list")
             }
 
             fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
                 Ok(Value::Integer(value))
             }
 
+            fn visit_u64<E>(self, value: u64) -> Result<Value, E>
+            where
+                E: serde::de::Error,
+            {
+                i64::try_from(value)
+                    .map(Value::Integer)
+                    .map_err(|_| E::custom("integer is too large"))
+            }
+
             fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
                 Ok(Value::Float(value))
             }
@@ -63,6 +73,17 @@ impl<'de> serde::Deserialize<'de> for Value {
                     Ok(Value::String(SmolStr::new(value)))
                 }
             }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(Value::List(values))
+            }
         }
 
         deserializer.deserialize_any(ValueVisitor)
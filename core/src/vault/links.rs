@@ -0,0 +1,187 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    frontmatter::Frontmatter,
+    markdown::{self, WikiLink},
+};
+
+use super::note_name;
+
+/// Wiki link with the source line it's written in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub link: WikiLink,
+    /// Trimmed line containing the link
+    pub context: String,
+}
+
+/// Link to a note from another one
+#[derive(Debug, Clone, Copy)]
+pub struct Backlink<'a> {
+    pub source: &'a Path,
+    pub link: &'a Link,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Note {
+    links: Vec<Link>,
+    aliases: Vec<String>,
+}
+
+/// Wiki links between notes of a directory
+#[derive(Debug, Default, Clone)]
+pub struct LinkIndex {
+    root: PathBuf,
+    notes: HashMap<PathBuf, Note>,
+}
+
+impl LinkIndex {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            notes: HashMap::new(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Re-indexes the note from its new text
+    pub fn update(&mut self, path: &Path, text: &str) {
+        let links = markdown::wiki_links(text)
+            .into_iter()
+            .map(|link| Link {
+                context: context(text, link.range.start),
+                link,
+            })
+            .collect();
+        let aliases = Frontmatter::parse(text)
+            .map(|frontmatter| {
+                frontmatter
+                    .aliases()
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        self.notes
+            .insert(path.to_path_buf(), Note { links, aliases });
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.notes.remove(path);
+    }
+
+    /// Links written in the note
+    pub fn outgoing(&self, path: &Path) -> &[Link] {
+        self.notes
+            .get(path)
+            .map(|note| note.links.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Links from other notes referring to the note, ordered by source path
+    pub fn backlinks(&self, path: &Path) -> Vec<Backlink<'_>> {
+        let mut backlinks: Vec<Backlink> = self
+            .notes
+            .iter()
+            .filter(|(source, _)| source.as_path() != path)
+            .flat_map(|(source, note)| {
+                note.links
+                    .iter()
+                    .filter(|link| self.refers_to(&link.link.target, path))
+                    .map(|link| Backlink { source, link })
+            })
+            .collect();
+
+        backlinks.sort_by(|a, b| {
+            a.source
+                .cmp(b.source)
+                .then(a.link.link.range.start.cmp(&b.link.link.range.start))
+        });
+        backlinks
+    }
+
+    /// Finds the note a link target refers to
+    pub fn resolve(&self, target: &str) -> Option<&Path> {
+        let mut paths: Vec<&PathBuf> = self.notes.keys().collect();
+        paths.sort();
+        paths
+            .into_iter()
+            .find(|path| self.refers_to(target, path))
+            .map(PathBuf::as_path)
+    }
+
+    /// Matches the target against note name, aliases or path relative to the root
+    fn refers_to(&self, target: &str, path: &Path) -> bool {
+        let target = target.trim();
+        let target = target.strip_suffix(".md").unwrap_or(target);
+
+        if target.contains('/') {
+            let relative = path.strip_prefix(&self.root).unwrap_or(path);
+            let relative = relative.with_extension("");
+            return relative
+                .to_string_lossy()
+                .replace('\\', "/")
+                .eq_ignore_ascii_case(target.trim_start_matches('/'));
+        }
+
+        note_name(path).eq_ignore_ascii_case(target)
+            || self.notes.get(path).is_some_and(|note| {
+                note.aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(target))
+            })
+    }
+}
+
+/// Returns trimmed line around the byte offset
+fn context(text: &str, offset: usize) -> String {
+    let start = text[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let end = text[offset..]
+        .find('\n')
+        .map(|i| offset + i)
+        .unwrap_or(text.len());
+    text[start..end].trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backlinks() {
+        let root = PathBuf::from("/vault");
+        let mut index = LinkIndex::new(&root);
+        index.update(
+            &root.join("a.md"),
+            "Links to [[B]]\nand [[dir/c|see c]] here\n",
+        );
+        index.update(
+            &root.join("b.md"),
+            "---\naliases: [Bee]\n---\nBack to [[a]]",
+        );
+        index.update(&root.join("dir/c.md"), "Mentions [[bee#Intro]]");
+
+        assert_eq!(index.outgoing(&root.join("a.md")).len(), 2);
+
+        let backlinks = index.backlinks(&root.join("b.md"));
+        let sources: Vec<&Path> = backlinks.iter().map(|b| b.source).collect();
+        assert_eq!(sources, vec![root.join("a.md"), root.join("dir/c.md")]);
+        assert_eq!(backlinks[0].link.context, "Links to [[B]]");
+
+        let backlinks = index.backlinks(&root.join("dir/c.md"));
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].link.context, "and [[dir/c|see c]] here");
+
+        // Re-indexing replaces old links
+        index.update(&root.join("a.md"), "No links");
+        assert_eq!(index.backlinks(&root.join("dir/c.md")).len(), 0);
+        assert_eq!(index.resolve("Bee"), Some(root.join("b.md").as_path()));
+    }
+}
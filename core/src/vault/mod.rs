@@ -0,0 +1,58 @@
+//! Indexes over markdown notes of the working directory
+
+pub mod links;
+pub mod tags;
+pub mod tasks;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::encoding;
+
+/// Lists markdown files under the directory recursively, skipping hidden entries
+pub fn markdown_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut directories = vec![root.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        let Ok(entries) = fs::read_dir(&directory) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                directories.push(path);
+            } else if is_markdown(&path) {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+/// Reads markdown files under the directory, skipping unreadable ones
+pub fn read_notes(root: &Path) -> impl Iterator<Item = (PathBuf, String)> {
+    markdown_files(root).into_iter().filter_map(|path| {
+        let (text, _) = encoding::decode(&fs::read(&path).ok()?);
+        Some((path, text))
+    })
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "md" || extension == "markdown")
+}
+
+/// Name the note is referred to by, its file stem
+pub fn note_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
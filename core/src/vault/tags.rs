@@ -0,0 +1,145 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use crate::{frontmatter::Frontmatter, markdown};
+
+/// Entry of the tag tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagNode {
+    /// Full tag like `project/alpha`
+    pub tag: String,
+    /// Last segment of the tag
+    pub label: String,
+    /// Nesting level, 0 for top level tags
+    pub depth: usize,
+    /// Number of notes carrying the tag or any tag nested in it
+    pub count: usize,
+}
+
+/// Inline and frontmatter tags of notes in a directory
+#[derive(Debug, Default, Clone)]
+pub struct TagIndex {
+    notes: HashMap<PathBuf, Vec<String>>,
+}
+
+impl TagIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Re-indexes the note from its new text
+    pub fn update(&mut self, path: &Path, text: &str) {
+        let mut tags: Vec<String> = Frontmatter::parse(text)
+            .map(|frontmatter| {
+                frontmatter
+                    .tags()
+                    .into_iter()
+                    .map(|tag| tag.trim_start_matches('#').to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        tags.extend(markdown::tags(text));
+        tags.sort();
+        tags.dedup();
+
+        if tags.is_empty() {
+            self.notes.remove(path);
+        } else {
+            self.notes.insert(path.to_path_buf(), tags);
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.notes.remove(path);
+    }
+
+    /// Tags of the note
+    pub fn tags_of(&self, path: &Path) -> &[String] {
+        self.notes.get(path).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// All tags with their parents, sorted so nested tags follow their parent
+    pub fn tree(&self) -> Vec<TagNode> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for tags in self.notes.values() {
+            // Each note counts once per tag, even if it has several nested ones
+            let mut prefixes: Vec<&str> = tags.iter().flat_map(|tag| prefixes(tag)).collect();
+            prefixes.sort();
+            prefixes.dedup();
+            for prefix in prefixes {
+                *counts.entry(prefix).or_default() += 1;
+            }
+        }
+
+        let mut nodes: Vec<TagNode> = counts
+            .into_iter()
+            .map(|(tag, count)| TagNode {
+                tag: tag.to_string(),
+                label: tag.rsplit('/').next().unwrap_or(tag).to_string(),
+                depth: tag.matches('/').count(),
+                count,
+            })
+            .collect();
+
+        // Compare by segments so `a/b` goes right after `a`, before `a-c`
+        nodes.sort_by(|a, b| a.tag.split('/').cmp(b.tag.split('/')));
+        nodes
+    }
+
+    /// Notes carrying the tag or a tag nested in it, sorted by path
+    pub fn notes(&self, tag: &str) -> Vec<&Path> {
+        let mut notes: Vec<&Path> = self
+            .notes
+            .iter()
+            .filter(|(_, tags)| tags.iter().any(|t| prefixes(t).any(|prefix| prefix == tag)))
+            .map(|(path, _)| path.as_path())
+            .collect();
+        notes.sort();
+        notes
+    }
+}
+
+/// Returns the tag and all its parents, like `a`, `a/b` for `a/b`
+fn prefixes(tag: &str) -> impl Iterator<Item = &str> {
+    tag.match_indices('/')
+        .map(|(i, _)| &tag[..i])
+        .chain(std::iter::once(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_tags() {
+        let mut index = TagIndex::new();
+        index.update(Path::new("a.md"), "---\ntags: [project/alpha]\n---\n#todo");
+        index.update(Path::new("b.md"), "#project/beta and #project/alpha/x");
+        index.update(Path::new("c.md"), "#project-notes");
+
+        let tree: Vec<(String, usize, usize)> = index
+            .tree()
+            .into_iter()
+            .map(|node| (node.tag, node.depth, node.count))
+            .collect();
+        assert_eq!(
+            tree,
+            vec![
+                (String::from("project"), 0, 2),
+                (String::from("project/alpha"), 1, 2),
+                (String::from("project/alpha/x"), 2, 1),
+                (String::from("project/beta"), 1, 1),
+                (String::from("project-notes"), 0, 1),
+                (String::from("todo"), 0, 1),
+            ]
+        );
+
+        assert_eq!(
+            index.notes("project/alpha"),
+            vec![Path::new("a.md"), Path::new("b.md")]
+        );
+        assert_eq!(index.notes("todo"), vec![Path::new("a.md")]);
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::markdown;
+
+/// Task list item of a note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    /// Item text without the due date
+    pub text: String,
+    pub done: bool,
+    /// Due date like `2026-10-20`
+    pub due: Option<String>,
+    /// Source line of the item
+    pub line: usize,
+}
+
+/// Task list items of notes in a directory
+#[derive(Debug, Default, Clone)]
+pub struct TaskIndex {
+    notes: HashMap<PathBuf, Vec<Task>>,
+}
+
+impl TaskIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Re-indexes the note from its new text
+    pub fn update(&mut self, path: &Path, text: &str) {
+        let tasks: Vec<Task> = markdown::tasks(text)
+            .into_iter()
+            .map(|item| {
+                let (text, due) = due_date(&item.text);
+                Task {
+                    text,
+                    done: item.checked,
+                    due,
+                    line: item.line,
+                }
+            })
+            .collect();
+
+        if tasks.is_empty() {
+            self.notes.remove(path);
+        } else {
+            self.notes.insert(path.to_path_buf(), tasks);
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.notes.remove(path);
+    }
+
+    pub fn tasks_of(&self, path: &Path) -> &[Task] {
+        self.notes.get(path).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Unfinished tasks grouped by note, ordered by path and line
+    pub fn open(&self) -> Vec<(&Path, Vec<&Task>)> {
+        let mut groups: Vec<(&Path, Vec<&Task>)> = self
+            .notes
+            .iter()
+            .map(|(path, tasks)| {
+                (
+                    path.as_path(),
+                    tasks.iter().filter(|task| !task.done).collect::<Vec<_>>(),
+                )
+            })
+            .filter(|(_, tasks)| !tasks.is_empty())
+            .collect();
+        groups.sort_by(|a, b| a.0.cmp(b.0));
+        groups
+    }
+}
+
+/// Flips the checkbox of the task item on the line, returning the new text
+pub fn toggle(text: &str, line: usize) -> Option<String> {
+    let start: usize = text.split_inclusive('\n').take(line).map(str::len).sum();
+    let end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+    let (offset, checked) = markdown::task_marker(&text[start..end])?;
+
+    let mut text = text.to_string();
+    let offset = start + offset;
+    text.replace_range(offset..offset + 1, if checked { " " } else { "x" });
+    Some(text)
+}
+
+/// Splits `@due(2026-10-20)` or `📅 2026-10-20` out of the task text
+fn due_date(text: &str) -> (String, Option<String>) {
+    let found = if let Some(start) = text.find("@due(") {
+        let date_start = start + "@due(".len();
+        text[date_start..].find(')').map(|len| {
+            (
+                start..date_start + len + 1,
+                &text[date_start..date_start + len],
+            )
+        })
+    } else if let Some(start) = text.find('📅') {
+        let rest = &text[start + '📅'.len_utf8()..];
+        let date = rest.trim_start();
+        let date_start = text.len() - date.len();
+        let len = date
+            .find(|c: char| !(c.is_ascii_digit() || c == '-'))
+            .unwrap_or(date.len());
+        Some((start..date_start + len, &text[date_start..date_start + len]))
+    } else {
+        None
+    };
+
+    match found {
+        Some((range, date)) if is_date(date.trim()) => {
+            let mut rest = String::from(text[..range.start].trim_end());
+            let tail = text[range.end..].trim();
+            if !tail.is_empty() {
+                rest.push(' ');
+                rest.push_str(tail);
+            }
+            (rest, Some(date.trim().to_string()))
+        }
+        _ => (text.trim().to_string(), None),
+    }
+}
+
+/// Checks `YYYY-MM-DD` shape
+fn is_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    parts.len() == 3
+        && [4, 2, 2]
+            .iter()
+            .zip(&parts)
+            .all(|(len, part)| part.len() == *len && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_tasks() {
+        let mut index = TaskIndex::new();
+        index.update(
+            Path::new("b.md"),
+            "# Plan\n\n- [ ] Write docs @due(2026-10-20)\n- [x] Done\n- [ ] Ship 📅 2026-11-01 soon\n",
+        );
+        index.update(Path::new("a.md"), "* [ ] First\n");
+        index.update(Path::new("c.md"), "- [x] Finished\n");
+
+        let open = index.open();
+        assert_eq!(open.len(), 2);
+        assert_eq!(open[0].0, Path::new("a.md"));
+
+        let tasks: Vec<(&str, Option<&str>, usize)> = open[1]
+            .1
+            .iter()
+            .map(|task| (task.text.as_str(), task.due.as_deref(), task.line))
+            .collect();
+        assert_eq!(
+            tasks,
+            vec![
+                ("Write docs", Some("2026-10-20"), 2),
+                ("Ship soon", Some("2026-11-01"), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn toggle_task() {
+        let text = "# Plan\n- [ ] one\n  - [x] two\n";
+        assert_eq!(
+            toggle(text, 1).as_deref(),
+            Some("# Plan\n- [x] one\n  - [x] two\n")
+        );
+        assert_eq!(
+            toggle(text, 2).as_deref(),
+            Some("# Plan\n- [ ] one\n  - [ ] two\n")
+        );
+        assert_eq!(toggle(text, 0), None);
+    }
+
+    #[test]
+    fn invalid_due_date() {
+        assert_eq!(
+            due_date("Call @due(tomorrow)"),
+            (String::from("Call @due(tomorrow)"), None)
+        );
+    }
+}
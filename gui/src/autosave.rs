@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use config::Config;
+use core::value::Value;
+
+/// Delay used when `editor.autosave_delay_ms` isn't set
+const DEFAULT_DELAY: Duration = Duration::from_millis(1000);
+
+/// When changed documents are saved without asking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Autosave {
+    Off,
+    /// Save after no edits were made for the delay
+    AfterDelay(Duration),
+    /// Save when the window loses focus or another tab is opened
+    OnFocusChange,
+}
+
+impl Autosave {
+    /// Reads `editor.autosave` and `editor.autosave_delay_ms` entries
+    pub fn from_config(config: &Config) -> Self {
+        let mode = match config.get("editor", "autosave") {
+            Some(Value::String(mode)) => mode,
+            _ => return Autosave::Off,
+        };
+
+        match mode.as_str() {
+            "after_delay" => {
+                let delay = match config.get("editor", "autosave_delay_ms") {
+                    Some(Value::Integer(ms)) if ms > 0 => Duration::from_millis(ms as u64),
+                    _ => DEFAULT_DELAY,
+                };
+                Autosave::AfterDelay(delay)
+            }
+            "on_focus_change" => Autosave::OnFocusChange,
+            "off" => Autosave::Off,
+            mode => {
+                log::warn!("Unknown autosave mode {mode:?}, autosave is off");
+                Autosave::Off
+            }
+        }
+    }
+}
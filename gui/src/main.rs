@@ -1,7 +1,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod autosave;
 mod util;
+mod watcher;
 
+use autosave::Autosave;
 use config::{
     workdir::{create_config_dir, create_workdir},
     Config,
@@ -10,16 +13,25 @@ use iced::{
     keyboard::{on_key_press, Key},
     widget::{
         row,
-        text_editor::{self, Content},
-        Container,
+        text_editor::{self, Content, Edit},
+        text_input, Container,
     },
-    Element, Settings, Subscription, Task,
+    window, Element, Event, Settings, Subscription, Task,
+};
+use state::{Conflict, FindBar, State};
+use watcher::FileEvent;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
 };
-use state::State;
-
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
-use crate::util::{get_file_name, open_file, pick_file, save_file};
+use crate::util::{
+    export_html, get_file_name, open_file, pick_file, pick_save_path, save_document,
+    toggle_task_in_file, Backups,
+};
 
 use plugin::{ExamplePlugin, Plugin, PluginHost, PluginId, PluginInfo};
 
@@ -28,14 +40,28 @@ use theming::{
     metadata::ThemeMetadata,
     Theme,
 };
-use widget::pane::{self, pane_stack};
+use widget::{
+    buffer,
+    editor::{cursor_offset, move_cursor, move_to_line, scroll_to_line, select, top_line},
+    pane::{
+        self, pane_stack,
+        text_editor::{find_input_id, ConflictMessage, FindMessage},
+    },
+};
 
 use core::{
     action::{Action, DocumentAction, FileAction, GenericAction, PaneAction},
+    buffer::{Buffer, Edit as BufferEdit, FormattedBuffer},
     document::{DocumentHandler, DocumentId, DocumentStore},
-    pane::{Pane, PaneModel},
+    encoding::{self, LineEnding, TextFormat},
+    frontmatter::Frontmatter,
+    history::{Change, History},
+    markdown,
+    pane::{Axis, ClosedDocument, ClosedPane, ClosedPanes, GroupId, Pane, PaneModel},
+    recovery::{self, Journal},
     smol_str::SmolStr,
     value::Value,
+    vault::{links::LinkIndex, read_notes, tags::TagIndex, tasks::TaskIndex},
     HotKey, Modifiers,
 };
 
@@ -43,10 +69,21 @@ type HotKeyHandler = dyn Fn(&State) -> AppMessage;
 
 static DEFAULT_THEME: &str = "core.light";
 
+/// Share of a split a resize hotkey moves its border by
+const RESIZE_STEP: f32 = 0.05;
+
+/// How often unsaved documents are written to the recovery journal
+const JOURNAL_INTERVAL: Duration = Duration::from_secs(5);
+
 pub struct App {
     state: State,
     plugin_host: PluginHost,
     hotkeys: HashMap<HotKey, Box<HotKeyHandler>>,
+    journal: Journal,
+    /// Start time of the run, keeping journal keys of untitled documents apart between runs
+    session: u128,
+    /// Revisions of documents last written to the journal
+    journaled: HashMap<DocumentId, usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -54,12 +91,45 @@ pub enum AppMessage {
     LoadPlugin(PluginId, bool),
     LoadTheme(ThemeID),
     AddTheme(ThemeID, Box<Theme>, ThemeMetadata<'static>),
-    OpenedFile(Result<(PathBuf, String), ()>),
+    OpenedFile(Result<(PathBuf, String, TextFormat), ()>),
+    OpenedFileAt(Result<(PathBuf, String, TextFormat), ()>, usize),
     GenericAction(GenericAction),
     Action(Action),
-    SavedFile(DocumentId),
+    /// Message from a split that isn't focused, focusing it first
+    FocusGroup(GroupId, Box<AppMessage>),
+    /// Document was written to the path with the text
+    SavedFile(DocumentId, PathBuf, Arc<String>),
+    SaveFailed(DocumentId, String),
+    ExportFailed(DocumentId, String),
+    /// Saves changed documents according to the autosave mode
+    Autosave,
+    /// Writes unsaved documents to the recovery journal
+    Journal,
+    JournalFailed(DocumentId, String),
+    FileEvent(FileEvent),
+    /// File of a reopened editor pane was read
+    ReopenedFile(Result<(PathBuf, String, TextFormat), ()>, Box<ClosedPane>),
+    /// Text of a watched file read after it changed
+    ReadChangedFile(Result<(PathBuf, String, TextFormat), ()>),
+    Conflict(ConflictMessage, DocumentId),
+    RestoreDocument(recovery::Key),
+    DiscardRecovery(recovery::Key),
     OpenDirectory(PathBuf),
+    IndexedVault(Box<LinkIndex>, Box<TagIndex>, Box<TaskIndex>),
+    /// Note was changed on disk outside of an editor
+    NoteChanged(PathBuf, String),
+    SelectTag(String),
+    OpenAtLine(PathBuf, usize),
+    ToggleTask(DocumentId),
+    ToggleTaskAt(PathBuf, usize),
+    /// Task item starting at the byte offset was clicked in the preview
+    TogglePreviewTask(DocumentId, usize),
     TextEditorAction(text_editor::Action, DocumentId),
+    Find(FindMessage, DocumentId),
+    JumpToLine(usize, DocumentId),
+    ToggleOutline,
+    Undo(DocumentId),
+    Redo(DocumentId),
     OnKeyPress(Key, iced::keyboard::Modifiers),
     None,
 }
@@ -77,23 +147,55 @@ impl App {
             Box::new(ExamplePlugin {}) as Box<dyn Plugin>,
         );
 
+        // ~/strelka/.config/recovery
+        let config_dir = match config.get("system", "config_dir") {
+            Some(Value::String(dir)) => PathBuf::from(dir.as_str()),
+            _ => create_workdir()
+                .and_then(|workdir| create_config_dir(&workdir))
+                .expect("Can't create config directory"),
+        };
+        let journal = Journal::new(config_dir.join("recovery"));
+        let recovery = journal.entries();
+
         let mut panes = PaneModel::new();
         {
             let id = panes.add(Pane::NewDocument);
             panes.open(&id);
         }
+        // Offer restoring documents left unsaved by a crash
+        if !recovery.is_empty() {
+            let id = panes.add(Pane::Recovery);
+            panes.open(&id);
+        }
 
         let state = State {
             documents: DocumentStore::new(),
             panes,
+            closed: ClosedPanes::default(),
             themes: Catalog::new(),
             config,
+            find: FindBar::default(),
+            show_outline: false,
+            links: LinkIndex::default(),
+            tags: TagIndex::default(),
+            tasks: TaskIndex::default(),
+            recovery,
+            conflicts: HashMap::new(),
+            save_errors: HashMap::new(),
+            selected_tag: None,
+            previews: HashMap::new(),
+            outlines: HashMap::new(),
         };
 
         let mut app = Self {
             state,
             plugin_host,
             hotkeys: HashMap::new(),
+            journal,
+            session: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |time| time.as_millis()),
+            journaled: HashMap::new(),
         };
 
         // Ctrl-o open file
@@ -105,6 +207,150 @@ impl App {
             |_: &State| AppMessage::Action(Action::new(FileAction::PickFile)),
         );
 
+        // Ctrl-s save open document
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::Ctrl,
+                key: 's',
+            },
+            |state: &State| {
+                if let Some(&Pane::Editor(id)) = state.panes.get_open() {
+                    AppMessage::Action(Action::new(DocumentAction::Save(id)))
+                } else {
+                    AppMessage::None
+                }
+            },
+        );
+
+        // Ctrl-Shift-s save open document to another file
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlShift,
+                key: 's',
+            },
+            |state: &State| {
+                if let Some(&Pane::Editor(id)) = state.panes.get_open() {
+                    AppMessage::Action(Action::new(DocumentAction::SaveAs(id)))
+                } else {
+                    AppMessage::None
+                }
+            },
+        );
+
+        // Ctrl-Alt-s save all changed documents
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlAlt,
+                key: 's',
+            },
+            |_: &State| AppMessage::Action(Action::new(DocumentAction::SaveAll)),
+        );
+
+        // Ctrl-n create untitled document
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::Ctrl,
+                key: 'n',
+            },
+            |_: &State| AppMessage::Action(Action::new(FileAction::NewFile)),
+        );
+
+        // Ctrl-Alt-v split the tab group side by side
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlAlt,
+                key: 'v',
+            },
+            |_: &State| AppMessage::Action(Action::new(PaneAction::Split(Axis::Row))),
+        );
+
+        // Ctrl-Alt-h split the tab group one above the other
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlAlt,
+                key: 'h',
+            },
+            |_: &State| AppMessage::Action(Action::new(PaneAction::Split(Axis::Column))),
+        );
+
+        // Ctrl-Alt-q close the focused split
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlAlt,
+                key: 'q',
+            },
+            |_: &State| AppMessage::Action(Action::new(PaneAction::CloseSplit)),
+        );
+
+        // Ctrl-Alt-. and Ctrl-Alt-, move focus between splits
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlAlt,
+                key: '.',
+            },
+            |_: &State| AppMessage::Action(Action::new(PaneAction::FocusNextSplit)),
+        );
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlAlt,
+                key: ',',
+            },
+            |_: &State| AppMessage::Action(Action::new(PaneAction::FocusPreviousSplit)),
+        );
+
+        // Ctrl-Alt-= and Ctrl-Alt-- grow and shrink the focused split
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlAlt,
+                key: '=',
+            },
+            |_: &State| AppMessage::Action(Action::new(PaneAction::Resize(RESIZE_STEP))),
+        );
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlAlt,
+                key: '-',
+            },
+            |_: &State| AppMessage::Action(Action::new(PaneAction::Resize(-RESIZE_STEP))),
+        );
+
+        // Alt-, and Alt-. move the open tab left and right
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::Alt,
+                key: ',',
+            },
+            |state: &State| move_open_tab(state, -1),
+        );
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::Alt,
+                key: '.',
+            },
+            |state: &State| move_open_tab(state, 1),
+        );
+
+        // Alt-m move the open tab to the next split
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::Alt,
+                key: 'm',
+            },
+            |state: &State| {
+                let groups = state.panes.layout().groups();
+                let next = groups
+                    .iter()
+                    .position(|&group| group == state.panes.focused())
+                    .map(|i| groups[(i + 1) % groups.len()]);
+                match (state.panes.get_open_id(), next) {
+                    (Some(&id), Some(group)) if group != state.panes.focused() => {
+                        AppMessage::Action(Action::new(PaneAction::MoveToSplit { id, group }))
+                    }
+                    _ => AppMessage::None,
+                }
+            },
+        );
+
         // Ctrl-d enable dark mode
         app.add_hotkey(
             HotKey {
@@ -147,6 +393,78 @@ impl App {
             |_state: &State| AppMessage::Action(Action::new(PaneAction::Add(Pane::Buffer))),
         );
 
+        // Ctrl-Shift-t reopen the last closed tab
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlShift,
+                key: 't',
+            },
+            |_: &State| AppMessage::Action(Action::new(PaneAction::ReopenClosed)),
+        );
+
+        // Ctrl-Shift-b open backlinks of the open document
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlShift,
+                key: 'b',
+            },
+            |state: &State| {
+                if let Some(&Pane::Editor(id)) = state.panes.get_open() {
+                    AppMessage::Action(Action::new(PaneAction::Add(Pane::Backlinks(id))))
+                } else {
+                    AppMessage::None
+                }
+            },
+        );
+
+        // Ctrl-Shift-p open preview of the open document
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlShift,
+                key: 'p',
+            },
+            |state: &State| {
+                if let Some(&Pane::Editor(id)) = state.panes.get_open() {
+                    AppMessage::Action(Action::new(PaneAction::Add(Pane::Preview(id))))
+                } else {
+                    AppMessage::None
+                }
+            },
+        );
+
+        // Ctrl-Shift-g open tags pane
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlShift,
+                key: 'g',
+            },
+            |_state: &State| AppMessage::Action(Action::new(PaneAction::Add(Pane::Tags))),
+        );
+
+        // Ctrl-Shift-l open tasks pane
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlShift,
+                key: 'l',
+            },
+            |_state: &State| AppMessage::Action(Action::new(PaneAction::Add(Pane::Tasks))),
+        );
+
+        // Ctrl-Shift-x toggle task item on the cursor line
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlShift,
+                key: 'x',
+            },
+            |state: &State| {
+                if let Some(&Pane::Editor(id)) = state.panes.get_open() {
+                    AppMessage::ToggleTask(id)
+                } else {
+                    AppMessage::None
+                }
+            },
+        );
+
         // Ctrl-, open config viewer pane
         app.add_hotkey(
             HotKey {
@@ -156,6 +474,75 @@ impl App {
             |_state: &State| AppMessage::Action(Action::new(PaneAction::Add(Pane::Config))),
         );
 
+        // Ctrl-f open find bar
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::Ctrl,
+                key: 'f',
+            },
+            |state: &State| {
+                if let Some(&Pane::Editor(id)) = state.panes.get_open() {
+                    AppMessage::Find(FindMessage::Open, id)
+                } else {
+                    AppMessage::None
+                }
+            },
+        );
+
+        // Ctrl-e export open document to HTML
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::Ctrl,
+                key: 'e',
+            },
+            |state: &State| {
+                if let Some(&Pane::Editor(id)) = state.panes.get_open() {
+                    AppMessage::Action(Action::new(DocumentAction::ExportHtml(id)))
+                } else {
+                    AppMessage::None
+                }
+            },
+        );
+
+        // Ctrl-Shift-o toggle outline sidebar
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlShift,
+                key: 'o',
+            },
+            |_state: &State| AppMessage::ToggleOutline,
+        );
+
+        // Ctrl-z undo last change
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::Ctrl,
+                key: 'z',
+            },
+            |state: &State| {
+                if let Some(&Pane::Editor(id)) = state.panes.get_open() {
+                    AppMessage::Undo(id)
+                } else {
+                    AppMessage::None
+                }
+            },
+        );
+
+        // Ctrl-Shift-z redo undone change
+        app.add_hotkey(
+            HotKey {
+                modifiers: Modifiers::CtrlShift,
+                key: 'z',
+            },
+            |state: &State| {
+                if let Some(&Pane::Editor(id)) = state.panes.get_open() {
+                    AppMessage::Redo(id)
+                } else {
+                    AppMessage::None
+                }
+            },
+        );
+
         let mut tasks = Vec::new();
 
         for id in app.plugin_host.get_plugin_ids() {
@@ -180,6 +567,10 @@ impl App {
         let apply_theme = Task::perform(async move { theme }, AppMessage::LoadTheme);
         tasks.push(read_themes.chain(apply_theme));
 
+        if let Some(Value::String(workdir)) = app.state.config.get("system", "workdir") {
+            tasks.push(index_vault(PathBuf::from(workdir.as_str())));
+        }
+
         (app, Task::batch(tasks))
     }
 
@@ -191,77 +582,489 @@ impl App {
         self.hotkeys.insert(hotkey, Box::new(func));
     }
 
-    fn title(&self) -> String {
-        String::from("Strelka")
+    /// Backup settings from the `system.backups` and `system.backup_dir` config entries
+    fn backups(&self) -> Backups {
+        let config = &self.state.config;
+        let count = match config.get("system", "backups") {
+            Some(Value::Integer(count)) => count.max(0) as usize,
+            _ => 0,
+        };
+        let directory = match config.get("system", "backup_dir") {
+            Some(Value::String(dir)) if !dir.is_empty() => Some(PathBuf::from(dir.as_str())),
+            _ => None,
+        };
+        let workdir = match config.get("system", "workdir") {
+            Some(Value::String(workdir)) => PathBuf::from(workdir.as_str()),
+            _ => PathBuf::new(),
+        };
+
+        Backups {
+            count,
+            directory,
+            workdir,
+        }
     }
 
-    fn perform_action(&mut self, action: GenericAction) -> Task<AppMessage> {
-        match action {
-            GenericAction::File(action) => match action {
-                FileAction::PickFile => {
-                    return Task::perform(pick_file(None), AppMessage::OpenedFile)
+    fn file_event(&mut self, event: FileEvent) -> Task<AppMessage> {
+        match event {
+            FileEvent::Changed(path) => {
+                if self.state.documents.find_by_path(&path).is_some() {
+                    return Task::perform(open_file(path), AppMessage::ReadChangedFile);
                 }
-                FileAction::OpenFileCurrentTab(path) => {
-                    return Task::perform(open_file(path), AppMessage::OpenedFile)
-                }
-                FileAction::OpenFileForceCurrentTab(path) => {
-                    return Task::perform(open_file(path), AppMessage::OpenedFile)
+            }
+            FileEvent::Removed(path) => {
+                let Some(id) = self.state.documents.find_by_path(&path) else {
+                    return Task::none();
+                };
+                // Some editors save by removing the file and writing a new one
+                if path.exists() {
+                    return Task::perform(open_file(path), AppMessage::ReadChangedFile);
                 }
-                FileAction::OpenFileNewTab(path) => {
-                    return Task::perform(open_file(path), AppMessage::OpenedFile)
+                self.state.conflicts.insert(id, Conflict::Deleted);
+            }
+            FileEvent::Renamed(from, to) => {
+                if let Some(id) = self.state.documents.find_by_path(&from) {
+                    if let Some(handler) = self.state.documents.get_mut(&id) {
+                        handler.filename = get_file_name(&to);
+                    }
+                    self.state.documents.set_path(&id, to);
+                    self.state.links.remove(&from);
+                    self.state.tags.remove(&from);
+                    self.state.tasks.remove(&from);
+                    self.forget_journal(&recovery::Key::File(from));
+                    self.reindex(id);
+                } else if self.state.documents.find_by_path(&to).is_some() {
+                    // File was replaced with another one, like atomic saves do
+                    return Task::perform(open_file(to), AppMessage::ReadChangedFile);
                 }
-            },
-            GenericAction::Pane(action) => match action {
-                PaneAction::Close(id) => {
-                    let pane = self.state.panes.remove(&id);
+            }
+        }
+        Task::none()
+    }
 
-                    // Close document if Editor pane was closed
-                    if let Some(Pane::Editor(doc_id)) = pane {
-                        self.state.documents.remove(&doc_id);
-                    }
+    fn resolve_conflict(&mut self, message: ConflictMessage, id: DocumentId) -> Task<AppMessage> {
+        let Some(handler) = self.state.documents.get_mut(&id) else {
+            return Task::none();
+        };
 
-                    // If there no panes left, create a NewDocument one
-                    if self.state.panes.count() == 0 {
-                        let id = self.state.panes.add(Pane::NewDocument);
-                        self.state.panes.open(&id);
+        match message {
+            ConflictMessage::ToggleDiff => {
+                if let Some(Conflict::Changed { theirs, diff }) = self.state.conflicts.get_mut(&id)
+                {
+                    *diff = match diff {
+                        Some(_) => None,
+                        None => Some(core::diff::lines(theirs, &handler.text_content.text())),
+                    };
+                }
+            }
+            ConflictMessage::KeepMine => {
+                match self.state.conflicts.remove(&id) {
+                    // Don't ask again about the text that is already seen
+                    Some(Conflict::Changed { theirs, .. }) => handler.saved_text = theirs,
+                    // Saving recreates the file
+                    Some(Conflict::Deleted) => {
+                        handler.changed = true;
+                        handler.edited_at = Some(Instant::now());
                     }
+                    None => {}
                 }
-                PaneAction::Open(id) => self.state.panes.open(&id),
-                PaneAction::Add(pane) => {
-                    let id = self.state.panes.add(pane);
-                    self.state.panes.open(&id);
+            }
+            ConflictMessage::TakeTheirs => match self.state.conflicts.remove(&id) {
+                Some(Conflict::Changed { theirs, .. }) => {
+                    let format = handler.format;
+                    reload(handler, theirs, format);
+                    self.reindex(id);
                 }
-                PaneAction::Replace(id, pane) => {
-                    self.state.panes.replace(&id, pane);
+                Some(Conflict::Deleted) => {
+                    // Nothing is left to save, so closing must not recreate the file
+                    handler.changed = false;
+                    if let Some(path) = handler.path.clone() {
+                        self.forget_journal(&recovery::Key::File(path.clone()));
+                        self.state.links.remove(&path);
+                        self.state.tags.remove(&path);
+                        self.state.tasks.remove(&path);
+                    }
+
+                    let panes: Vec<_> = self
+                        .state
+                        .panes
+                        .list()
+                        .into_iter()
+                        .filter(|(_, pane)| **pane == Pane::Editor(id))
+                        .map(|(pane, _)| *pane)
+                        .collect();
+                    return Task::batch(panes.into_iter().map(|pane| {
+                        Task::done(AppMessage::Action(Action::new(PaneAction::Close(pane))))
+                    }));
                 }
+                None => {}
             },
-            GenericAction::Document(action) => match action {
-                DocumentAction::Add(handler) => {
-                    let content = Content::with_text(&handler.text_content);
-                    let handler = DocumentHandler {
-                        text_content: content,
-                        path: handler.path.clone(),
-                        filename: handler.filename.clone(),
-                        changed: handler.changed,
-                    };
-                    self.state.documents.add(handler);
-                }
-                DocumentAction::Open(id) => {
-                    let pane = Pane::Editor(id);
-                    return Task::done(AppMessage::Action(Action::new(PaneAction::Add(pane))));
-                }
-                DocumentAction::Save(id) => {
-                    if let Some(handler) = self.state.documents.get(&id) {
-                        let message = AppMessage::SavedFile(id);
+        }
+        Task::none()
+    }
+
+    /// Updates vault indexes with the document text
+    fn reindex(&mut self, id: DocumentId) {
+        if let Some(handler) = self.state.documents.get(&id) {
+            let Some(path) = &handler.path else {
+                return;
+            };
+            let text = handler.text_content.text();
+            self.state.links.update(path, &text);
+            self.state.tags.update(path, &text);
+            self.state.tasks.update(path, &text);
+        }
+    }
+
+    /// Removes journaled text of the file, it's either saved or discarded
+    fn forget_journal(&self, key: &recovery::Key) {
+        if let Err(err) = self.journal.remove(key) {
+            log::warn!("Can't remove journal of {key:?}: {err}");
+        }
+    }
+
+    /// Journal key of the document, its file or a generated one for untitled documents
+    fn journal_key(&self, id: DocumentId) -> Option<recovery::Key> {
+        let handler = self.state.documents.get(&id)?;
+        Some(match &handler.path {
+            Some(path) => recovery::Key::File(path.clone()),
+            None => recovery::Key::Untitled(format!("{}-{id}", self.session)),
+        })
+    }
+
+    /// First free name of an untitled document
+    fn untitled_name(&self) -> String {
+        let taken: Vec<String> = self
+            .state
+            .documents
+            .untitled()
+            .filter_map(|id| self.state.documents.get(&id))
+            .map(|handler| handler.filename.clone())
+            .collect();
+        (1..)
+            .map(|n| match n {
+                1 => String::from("Untitled"),
+                n => format!("Untitled {n}"),
+            })
+            .find(|name| !taken.contains(name))
+            .unwrap_or_default()
+    }
+
+    fn autosave(&self) -> Autosave {
+        Autosave::from_config(&self.state.config)
+    }
+
+    /// Saves the document through the save action if it has unsaved edits.
+    /// Untitled documents are skipped, they can't be saved without asking for a file.
+    fn save_changed(&mut self, id: DocumentId) -> Task<AppMessage> {
+        match self.state.documents.get(&id) {
+            Some(handler) if handler.changed && handler.path.is_some() => {
+                self.perform_action(DocumentAction::Save(id).into())
+            }
+            _ => Task::none(),
+        }
+    }
+
+    /// Saves the open document before another tab is opened in `on_focus_change` mode
+    fn save_on_focus_change(&mut self) -> Task<AppMessage> {
+        match (self.autosave(), self.state.panes.get_open()) {
+            (Autosave::OnFocusChange, Some(&Pane::Editor(id))) => self.save_changed(id),
+            _ => Task::none(),
+        }
+    }
+
+    fn title(&self) -> String {
+        String::from("Strelka")
+    }
+
+    fn perform_action(&mut self, action: GenericAction) -> Task<AppMessage> {
+        match action {
+            GenericAction::File(action) => match action {
+                FileAction::PickFile => {
+                    return Task::perform(pick_file(None), AppMessage::OpenedFile)
+                }
+                FileAction::NewFile => {
+                    let handler = DocumentHandler {
+                        text_content: Content::new(),
+                        path: None,
+                        filename: self.untitled_name(),
+                        ..Default::default()
+                    };
+                    let id = self.state.documents.add(handler);
+                    self.show_document(id);
+                }
+                FileAction::OpenFileCurrentTab(path)
+                | FileAction::OpenFileForceCurrentTab(path)
+                | FileAction::OpenFileNewTab(path) => {
+                    // Already open files are shown instead of being read again
+                    if let Some(id) = self.state.documents.find_by_path(&path) {
+                        self.show_document(id);
+                        return Task::none();
+                    }
+                    return Task::perform(open_file(path), AppMessage::OpenedFile);
+                }
+            },
+            GenericAction::Pane(action) => match action {
+                PaneAction::Close(id) => {
+                    let position = self.state.panes.position(&id);
+                    let pane = self.state.panes.remove(&id);
+                    if let (Some(pane), Some((group, index))) = (pane, position) {
+                        self.remember_closed(pane, group, index);
+                    }
+
+                    // Close document if Editor pane was closed, autosaving it first
+                    let mut save = Task::none();
+                    let shown = |doc_id| {
+                        self.state
+                            .panes
+                            .list()
+                            .into_iter()
+                            .any(|(_, pane)| *pane == Pane::Editor(doc_id))
+                    };
+                    // Document stays open while another pane shows it
+                    let closed = pane.and_then(|pane| match pane {
+                        Pane::Editor(doc_id) if !shown(doc_id) => Some(doc_id),
+                        _ => None,
+                    });
+                    if let Some(doc_id) = closed {
+                        let titled = self
+                            .state
+                            .documents
+                            .get(&doc_id)
+                            .is_some_and(|handler| handler.path.is_some());
+                        if titled && self.autosave() != Autosave::Off {
+                            save = self.save_changed(doc_id);
+                        } else if let Some(key) = self.journal_key(doc_id) {
+                            // Closing without saving drops the edits on purpose
+                            self.forget_journal(&key);
+                        }
+                        self.state.documents.remove(&doc_id);
+                        self.state.save_errors.remove(&doc_id);
+                        self.state.conflicts.remove(&doc_id);
+                    }
+
+                    // If there no panes left, create a NewDocument one
+                    if self.state.panes.count() == 0 {
+                        let id = self.state.panes.add(Pane::NewDocument);
+                        self.state.panes.open(&id);
+                    }
+                    return save;
+                }
+                PaneAction::Open(id) => {
+                    let save = self.save_on_focus_change();
+                    self.state.panes.open(&id);
+                    return save;
+                }
+                PaneAction::Add(pane) => {
+                    let save = self.save_on_focus_change();
+                    let id = self.state.panes.add(pane);
+                    self.state.panes.open(&id);
+                    return save;
+                }
+                PaneAction::Replace(id, pane) => {
+                    self.state.panes.replace(&id, pane);
+                }
+                PaneAction::Move { id, index } => self.state.panes.move_pane(&id, index),
+                PaneAction::MoveToSplit { id, group } => {
+                    self.state.panes.move_to_group(&id, &group)
+                }
+                PaneAction::Split(axis) => {
+                    self.state.panes.split(axis);
+                }
+                PaneAction::CloseSplit => {
+                    if self.state.panes.group_count() < 2 {
+                        return Task::none();
+                    }
+                    // Closing the last tab of a split closes the split itself
+                    let group = self.state.panes.focused();
+                    let tabs = self
+                        .state
+                        .panes
+                        .group(&group)
+                        .map(|group| group.tabs().to_vec())
+                        .unwrap_or_default();
+                    return Task::batch(
+                        tabs.into_iter()
+                            .map(|pane| self.perform_action(PaneAction::Close(pane).into())),
+                    );
+                }
+                PaneAction::ReopenClosed => {
+                    if let Some(closed) = self.state.closed.pop() {
+                        return self.reopen(closed);
+                    }
+                }
+                PaneAction::FocusNextSplit => self.state.panes.focus_next(),
+                PaneAction::FocusPreviousSplit => self.state.panes.focus_previous(),
+                PaneAction::Resize(delta) => self.state.panes.resize(delta),
+            },
+            GenericAction::Document(action) => match action {
+                DocumentAction::Add(handler) => {
+                    let content = Content::with_text(&handler.text_content);
+                    let handler = DocumentHandler {
+                        text_content: content,
+                        path: handler.path.clone(),
+                        filename: handler.filename.clone(),
+                        changed: handler.changed,
+                        edited_at: None,
+                        saved_text: handler.text_content.clone(),
+                        format: handler.format,
+                        history: History::new(),
+                        revision: 0,
+                        metadata: Frontmatter::parse(&handler.text_content),
+                    };
+                    self.state.documents.add(handler);
+                }
+                DocumentAction::Open(id) => {
+                    let pane = Pane::Editor(id);
+                    return Task::done(AppMessage::Action(Action::new(PaneAction::Add(pane))));
+                }
+                DocumentAction::Save(id) => {
+                    let backups = self.backups();
+                    if let Some(handler) = self.state.documents.get_mut(&id) {
+                        let Some(path) = handler.path.clone() else {
+                            return self.perform_action(DocumentAction::SaveAs(id).into());
+                        };
+
+                        // Frontmatter is a part of the text, so it's saved as written
+                        let mut text = handler.text_content.text();
+                        if handler.format.line_ending == LineEnding::Mixed {
+                            text = encoding::keep_endings(&text, &handler.saved_text);
+                        }
+                        let text = Arc::new(text);
+                        // Autosave doesn't start another save while this one runs
+                        handler.edited_at = None;
+
+                        return Task::perform(
+                            save_document(path.clone(), text.clone(), handler.format, backups),
+                            move |result| match result {
+                                Ok(()) => AppMessage::SavedFile(id, path.clone(), text.clone()),
+                                Err(err) => AppMessage::SaveFailed(id, err.to_string()),
+                            },
+                        );
+                    }
+                }
+                DocumentAction::SaveAs(id) => {
+                    if let Some(handler) = self.state.documents.get(&id) {
+                        let name = match handler.path {
+                            Some(_) => handler.filename.clone(),
+                            None => format!("{}.md", handler.filename),
+                        };
                         return Task::perform(
-                            save_file(handler.path.clone(), Arc::new(handler.text_content.text())),
-                            move |_| message.clone(),
+                            pick_save_path(handler.path.clone(), name),
+                            move |path| match path {
+                                Some(path) => AppMessage::Action(Action::new(
+                                    DocumentAction::SaveTo(id, path),
+                                )),
+                                None => AppMessage::None,
+                            },
+                        );
+                    }
+                }
+                DocumentAction::SaveTo(id, path) => {
+                    if self.state.documents.get(&id).is_none() {
+                        return Task::none();
+                    }
+                    // Two documents of one file would overwrite each other
+                    if self
+                        .state
+                        .documents
+                        .find_by_path(&path)
+                        .is_some_and(|open| open != id)
+                    {
+                        self.state.save_errors.insert(
+                            id,
+                            format!("Not saved: {} is open in another tab", path.display()),
                         );
+                        return Task::none();
+                    }
+
+                    // The old file keeps its own text
+                    if let Some(key) = self.journal_key(id) {
+                        self.forget_journal(&key);
+                    }
+                    if let Some(old) = self
+                        .state
+                        .documents
+                        .get(&id)
+                        .and_then(|handler| handler.path.clone())
+                    {
+                        self.state.links.remove(&old);
+                        self.state.tags.remove(&old);
+                        self.state.tasks.remove(&old);
+                    }
+                    if let Some(handler) = self.state.documents.get_mut(&id) {
+                        handler.filename = get_file_name(&path);
+                    }
+                    self.state.documents.set_path(&id, path);
+                    self.state.conflicts.remove(&id);
+                    return self.perform_action(DocumentAction::Save(id).into());
+                }
+                DocumentAction::SaveAll => {
+                    let mut ids: Vec<DocumentId> = self
+                        .state
+                        .documents
+                        .iter()
+                        .filter(|(_, handler)| handler.changed)
+                        .map(|(id, _)| *id)
+                        .collect();
+                    ids.sort();
+
+                    // Untitled documents are asked for one by one, so dialogs don't pile up
+                    let (untitled, titled): (Vec<_>, Vec<_>) = ids.into_iter().partition(|id| {
+                        self.state
+                            .documents
+                            .get(id)
+                            .is_some_and(|handler| handler.path.is_none())
+                    });
+                    let saves = Task::batch(
+                        titled
+                            .into_iter()
+                            .map(|id| self.perform_action(DocumentAction::Save(id).into())),
+                    );
+                    return untitled.into_iter().fold(saves, |tasks, id| {
+                        tasks.chain(self.perform_action(DocumentAction::SaveAs(id).into()))
+                    });
+                }
+                DocumentAction::SetFormat(id, format) => {
+                    if let Some(handler) = self.state.documents.get_mut(&id) {
+                        if handler.format != format {
+                            handler.format = format;
+                            handler.changed = true;
+                            handler.edited_at = Some(Instant::now());
+                        }
                     }
                 }
                 DocumentAction::Remove(id) => {
                     self.state.documents.remove(&id);
                 }
+                DocumentAction::ExportHtml(id) => {
+                    if let Some(handler) = self.state.documents.get(&id) {
+                        // Frontmatter is left out by the parser
+                        let buffer = Buffer::new(handler.text_content.text());
+                        let html = core::export::to_html(
+                            &FormattedBuffer::from_buffer(&buffer),
+                            handler.title().unwrap_or(&handler.filename),
+                            &self.state.get_theme().to_css(),
+                        );
+                        let name = Path::new(&handler.filename)
+                            .with_extension("html")
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .unwrap_or("export.html")
+                            .to_owned();
+
+                        return Task::perform(export_html(name, Arc::new(html)), move |result| {
+                            match result {
+                                Ok(Some(path)) => {
+                                    log::info!("Exported document to {path:?}");
+                                    AppMessage::None
+                                }
+                                Ok(None) => AppMessage::None,
+                                Err(err) => AppMessage::ExportFailed(id, err.to_string()),
+                            }
+                        });
+                    }
+                }
             },
         }
         Task::none()
@@ -270,6 +1073,108 @@ impl App {
     fn update(&mut self, message: AppMessage) -> Task<AppMessage> {
         log::info!("Handling message: {message:?}");
 
+        let task = self.handle(message);
+        self.count_matches();
+        self.update_previews();
+        self.update_outlines();
+        task
+    }
+
+    /// Lists headings of documents in shown editors again after their text changed
+    fn update_outlines(&mut self) {
+        if !self.state.show_outline {
+            self.state.outlines.clear();
+            return;
+        }
+
+        let panes = &self.state.panes;
+        let shown: Vec<DocumentId> = panes
+            .layout()
+            .groups()
+            .into_iter()
+            .filter_map(|group| panes.group(&group)?.open())
+            .filter_map(|pane| match panes.get(&pane) {
+                Some(Pane::Editor(id)) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        self.state.outlines.retain(|id, _| shown.contains(id));
+
+        for id in shown {
+            let Some(handler) = self.state.documents.get(&id) else {
+                continue;
+            };
+            if self
+                .state
+                .outlines
+                .get(&id)
+                .is_some_and(|(revision, _)| *revision == handler.revision)
+            {
+                continue;
+            }
+            let outline = markdown::outline(&handler.text_content.text());
+            self.state.outlines.insert(id, (handler.revision, outline));
+        }
+    }
+
+    /// Parses documents shown in preview panes again after their text changed
+    fn update_previews(&mut self) {
+        let shown: Vec<DocumentId> = self
+            .state
+            .panes
+            .list()
+            .into_iter()
+            .filter_map(|(_, pane)| match pane {
+                Pane::Preview(id) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        self.state.previews.retain(|id, _| shown.contains(id));
+
+        for id in shown {
+            let Some(handler) = self.state.documents.get(&id) else {
+                continue;
+            };
+            if let Some((revision, source, formatted)) = self.state.previews.get_mut(&id) {
+                if *revision != handler.revision {
+                    // Only blocks around the changed part are parsed again
+                    let edit = BufferEdit::between(&source.text(), &handler.text_content.text());
+                    source.apply(&edit);
+                    formatted.update(source, &edit);
+                    *revision = handler.revision;
+                }
+                continue;
+            }
+            let buffer = Buffer::new(handler.text_content.text());
+            let formatted = FormattedBuffer::from_buffer(&buffer);
+            self.state
+                .previews
+                .insert(id, (handler.revision, buffer, formatted));
+        }
+    }
+
+    /// Counts find matches in the open editor when the query or its text changed
+    fn count_matches(&mut self) {
+        let find = &mut self.state.find;
+        let Some(&Pane::Editor(id)) = self.state.panes.get_open() else {
+            return;
+        };
+        let Some(handler) = self.state.documents.get(&id) else {
+            return;
+        };
+        if !find.visible || find.counted == Some((id, handler.revision)) {
+            return;
+        }
+
+        find.matches = find
+            .query
+            .build()
+            .ok()
+            .map(|searcher| searcher.find_all(&handler.text_content.text()).len());
+        find.counted = Some((id, handler.revision));
+    }
+
+    fn handle(&mut self, message: AppMessage) -> Task<AppMessage> {
         match message {
             AppMessage::None => {}
 
@@ -285,16 +1190,201 @@ impl App {
 
             AppMessage::GenericAction(action) => return self.perform_action(action),
 
+            AppMessage::FocusGroup(group, message) => {
+                self.state.panes.focus(&group);
+                return self.handle(*message);
+            }
+
             AppMessage::AddTheme(id, theme, metadata) => {
                 self.state.themes.insert(id, *theme, metadata);
             }
 
             AppMessage::LoadTheme(id) => self.state.set_theme(id),
 
-            AppMessage::SavedFile(id) => {
-                if let Some(handler) = self.state.documents.get_mut(&id) {
+            AppMessage::SavedFile(id, path, text) => {
+                self.state.save_errors.remove(&id);
+                self.state.links.update(&path, &text);
+                self.state.tags.update(&path, &text);
+                self.state.tasks.update(&path, &text);
+
+                // Closing a tab autosaves the document and drops it before the save is done
+                let Some(handler) = self.state.documents.get_mut(&id) else {
+                    self.forget_journal(&recovery::Key::File(path));
+                    return Task::none();
+                };
+                handler.saved_text = text.to_string();
+                handler.metadata = Frontmatter::parse(&text);
+
+                // Edits made while saving still need a save,
+                // the editor has no CRLF endings of mixed text
+                if handler.text_content.text() == text.replace("\r\n", "\n") {
                     handler.changed = false;
+                    handler.history.mark_saved();
+                    self.forget_journal(&recovery::Key::File(path));
+                }
+            }
+
+            AppMessage::Journal => {
+                let documents = &self.state.documents;
+                self.journaled.retain(|id, _| documents.get(id).is_some());
+
+                let mut writes = Vec::new();
+                for (&id, handler) in self.state.documents.iter() {
+                    if !handler.changed || self.journaled.get(&id) == Some(&handler.revision) {
+                        continue;
+                    }
+                    let Some(key) = self.journal_key(id) else {
+                        continue;
+                    };
+                    self.journaled.insert(id, handler.revision);
+
+                    let journal = self.journal.clone();
+                    let text = handler.text_content.text();
+                    writes.push(Task::perform(
+                        async move { journal.write(&key, &text).map_err(|err| err.to_string()) },
+                        move |result| match result {
+                            Ok(()) => AppMessage::None,
+                            Err(err) => AppMessage::JournalFailed(id, err),
+                        },
+                    ));
                 }
+                return Task::batch(writes);
+            }
+
+            AppMessage::JournalFailed(id, error) => {
+                log::warn!("Can't journal document {id}: {error}");
+                // Written again on the next tick
+                self.journaled.remove(&id);
+            }
+
+            AppMessage::FileEvent(event) => return self.file_event(event),
+
+            AppMessage::ReopenedFile(result, closed) => {
+                let Some(document) = closed.document.clone() else {
+                    return Task::none();
+                };
+                let path = document.path.clone();
+                match self.restore_closed(document, result.ok()) {
+                    Some(id) => self.insert_closed(Pane::Editor(id), &closed),
+                    None => log::warn!("Can't reopen {path:?}, the file is gone"),
+                }
+            }
+
+            AppMessage::ReadChangedFile(result) => {
+                let Ok((path, theirs, format)) = result else {
+                    return Task::none();
+                };
+                let Some(id) = self.state.documents.find_by_path(&path) else {
+                    return Task::none();
+                };
+                let Some(handler) = self.state.documents.get_mut(&id) else {
+                    return Task::none();
+                };
+
+                // Our own saves and touches without changes land here too,
+                // a save may not have reported back yet
+                if theirs == handler.saved_text || theirs == handler.text_content.text() {
+                    return Task::none();
+                }
+
+                if handler.changed {
+                    self.state
+                        .conflicts
+                        .insert(id, Conflict::Changed { theirs, diff: None });
+                } else {
+                    reload(handler, theirs, format);
+                    self.state.conflicts.remove(&id);
+                    self.reindex(id);
+                }
+            }
+
+            AppMessage::Conflict(message, id) => return self.resolve_conflict(message, id),
+
+            AppMessage::RestoreDocument(key) => {
+                let Some(i) = self.state.recovery.iter().position(|e| e.key == key) else {
+                    return Task::none();
+                };
+                let entry = self.state.recovery.remove(i);
+
+                // Restored text stays in the journal until it's saved
+                let id = match entry.key {
+                    recovery::Key::File(path) => {
+                        let saved = entry.saved.unwrap_or_default();
+                        self.open_document(path, saved, entry.format)
+                    }
+                    recovery::Key::Untitled(_) => {
+                        let handler = DocumentHandler {
+                            text_content: Content::new(),
+                            path: None,
+                            filename: self.untitled_name(),
+                            ..Default::default()
+                        };
+                        let id = self.state.documents.add(handler);
+                        self.show_document(id);
+                        id
+                    }
+                };
+                if let Some(handler) = self.state.documents.get_mut(&id) {
+                    let current = handler.text_content.text();
+                    handler.history.record(Change::Other, || current.clone());
+                    restore(handler, &current, entry.text.clone());
+                }
+
+                // Untitled document of this run journals under its own key
+                if let Some(new) = self.journal_key(id).filter(|new| *new != key) {
+                    match self.journal.write(&new, &entry.text) {
+                        Ok(()) => self.forget_journal(&key),
+                        Err(err) => log::warn!("Can't journal {new:?}: {err}"),
+                    }
+                }
+            }
+
+            AppMessage::DiscardRecovery(key) => {
+                self.state.recovery.retain(|entry| entry.key != key);
+                self.forget_journal(&key);
+            }
+
+            AppMessage::ExportFailed(id, error) => {
+                log::warn!("Can't export document {id}: {error}");
+                // Shown where save errors are, until the next save
+                self.state
+                    .save_errors
+                    .insert(id, format!("Not exported: {error}"));
+            }
+
+            AppMessage::SaveFailed(id, error) => {
+                log::warn!("Can't save document {id}: {error}");
+                self.state
+                    .save_errors
+                    .insert(id, format!("Not saved: {error}"));
+                // Autosave tries again after the delay
+                if let Some(handler) = self.state.documents.get_mut(&id) {
+                    handler.edited_at.get_or_insert_with(Instant::now);
+                }
+            }
+
+            AppMessage::Autosave => {
+                let now = Instant::now();
+                let delay = match self.autosave() {
+                    Autosave::Off => return Task::none(),
+                    Autosave::AfterDelay(delay) => delay,
+                    Autosave::OnFocusChange => Duration::ZERO,
+                };
+
+                let mut ids: Vec<DocumentId> = self
+                    .state
+                    .documents
+                    .iter()
+                    .filter(|(_, handler)| {
+                        handler
+                            .edited_at
+                            .is_some_and(|edited| now.duration_since(edited) >= delay)
+                    })
+                    .map(|(id, _)| *id)
+                    .collect();
+                ids.sort();
+
+                return Task::batch(ids.into_iter().map(|id| self.save_changed(id)));
             }
 
             AppMessage::OnKeyPress(key, modifiers) => {
@@ -313,40 +1403,119 @@ impl App {
 
             AppMessage::TextEditorAction(action, document) => {
                 if let Some(handler) = self.state.documents.get_mut(&document) {
-                    if action.is_edit() {
-                        handler.changed = true;
+                    let click = matches!(action, text_editor::Action::Click(_));
+                    perform(handler, action);
+
+                    // Clicking the checkbox of a task item flips it
+                    if click {
+                        let offset = cursor_offset(&handler.text_content);
+                        let line = handler.text_content.cursor_position().0;
+                        let on_marker = task_at(handler, line)
+                            .is_some_and(|(marker, _)| offset == marker || offset == marker + 1);
+                        if on_marker {
+                            self.toggle_task(document, line);
+                        }
                     }
-                    handler.text_content.perform(action);
                 }
             }
 
-            // TODO: Should accept an document id and fill it's handler with content
-            AppMessage::OpenedFile(result) => {
-                if let Ok((path, content)) = result {
-                    let handler = DocumentHandler {
-                        text_content: Content::with_text(&content),
-                        path: path.clone(),
-                        filename: get_file_name(&path),
-                        changed: false,
-                    };
+            AppMessage::ToggleTask(document) => {
+                if let Some(handler) = self.state.documents.get(&document) {
+                    let line = handler.text_content.cursor_position().0;
+                    self.toggle_task(document, line);
+                }
+            }
 
-                    let doc_id = self.state.documents.add(handler);
-                    let pane = Pane::Editor(doc_id);
+            AppMessage::TogglePreviewTask(document, offset) => {
+                if let Some(handler) = self.state.documents.get(&document) {
+                    let text = handler.text_content.text();
+                    let line = text[..offset.min(text.len())].matches('\n').count();
+                    self.toggle_task(document, line);
+                }
+            }
 
-                    // If opened pane is NewDocument, replace it with Editor pane
-                    // otherwise add new one with Editor
-                    if let Some(&Pane::NewDocument) = self.state.panes.get_open() {
-                        self.state.panes.replace(
-                            &self.state.panes.get_open_id().cloned().unwrap_or(0usize),
-                            pane,
-                        );
-                    } else {
-                        let pane_id = self.state.panes.add(pane);
-                        self.state.panes.open(&pane_id);
+            AppMessage::ToggleTaskAt(path, line) => {
+                if let Some(document) = self.state.documents.find_by_path(&path) {
+                    self.toggle_task(document, line);
+                } else {
+                    return Task::perform(
+                        toggle_task_in_file(path, line, self.backups()),
+                        |result| match result {
+                            Ok((path, text)) => AppMessage::NoteChanged(path, text),
+                            Err(()) => AppMessage::None,
+                        },
+                    );
+                }
+            }
+
+            AppMessage::NoteChanged(path, text) => {
+                self.state.links.update(&path, &text);
+                self.state.tags.update(&path, &text);
+                self.state.tasks.update(&path, &text);
+            }
+
+            AppMessage::OpenAtLine(path, line) => {
+                let Some(document) = self.state.documents.find_by_path(&path) else {
+                    return Task::perform(open_file(path), move |result| {
+                        AppMessage::OpenedFileAt(result, line)
+                    });
+                };
+
+                self.show_document(document);
+                return Task::done(AppMessage::JumpToLine(line, document));
+            }
+
+            AppMessage::JumpToLine(line, document) => {
+                if let Some(handler) = self.state.documents.get_mut(&document) {
+                    move_to_line(&mut handler.text_content, line);
+                    handler.history.break_group();
+                }
+            }
+
+            AppMessage::ToggleOutline => self.state.show_outline = !self.state.show_outline,
+
+            AppMessage::Undo(document) => {
+                if let Some(handler) = self.state.documents.get_mut(&document) {
+                    let current = handler.text_content.text();
+                    if let Some(text) = handler.history.undo(current.clone()) {
+                        restore(handler, &current, text);
                     }
                 }
             }
 
+            AppMessage::Redo(document) => {
+                if let Some(handler) = self.state.documents.get_mut(&document) {
+                    let current = handler.text_content.text();
+                    if let Some(text) = handler.history.redo(current.clone()) {
+                        restore(handler, &current, text);
+                    }
+                }
+            }
+
+            AppMessage::Find(message, document) => return self.find(message, document),
+
+            // TODO: Should accept an document id and fill it's handler with content
+            AppMessage::OpenedFile(result) => {
+                if let Ok((path, content, format)) = result {
+                    self.open_document(path, content, format);
+                }
+            }
+
+            AppMessage::OpenedFileAt(result, line) => {
+                if let Ok((path, content, format)) = result {
+                    let document = self.open_document(path, content, format);
+                    return Task::done(AppMessage::JumpToLine(line, document));
+                }
+            }
+
+            AppMessage::IndexedVault(links, tags, tasks) => {
+                self.state.links = *links;
+                self.state.tags = *tags;
+                self.state.tasks = *tasks;
+            }
+
+            AppMessage::SelectTag(tag) => self.state.selected_tag = Some(tag),
+
             AppMessage::OpenDirectory(path) => {
                 if path.is_dir() {
                     let path: PathBuf = path.canonicalize().unwrap_or_default();
@@ -356,42 +1525,325 @@ impl App {
                         "workdir",
                         Value::String(SmolStr::new(path.to_str().unwrap_or_default())),
                     );
+                    return index_vault(path);
                 }
             }
         }
         Task::none()
     }
 
-    fn view(&self) -> Element<AppMessage, Theme> {
-        let mut grid_elements = Vec::new();
-        grid_elements.push(
-            pane_stack::pane_stack(&self.state).map(|msg| -> AppMessage {
-                match msg {
-                    pane_stack::Message::NewDocument(pane::new_document::Message::PickFile) => {
-                        AppMessage::Action(Action::new(FileAction::PickFile))
-                    }
+    /// Adds the document and shows it in an editor pane
+    fn open_document(&mut self, path: PathBuf, content: String, format: TextFormat) -> DocumentId {
+        let id = self.add_document(path, content, format);
+        self.show_document(id);
+        id
+    }
 
-                    pane_stack::Message::NewPane(pane) => {
-                        AppMessage::Action(Action::new(PaneAction::Add(pane)))
-                    }
+    /// Adds the document of the file without showing it
+    fn add_document(&mut self, path: PathBuf, content: String, format: TextFormat) -> DocumentId {
+        // The open document may already have edits, so it wins over the file
+        if let Some(id) = self.state.documents.find_by_path(&path) {
+            return id;
+        }
 
-                    pane_stack::Message::OpenPane(id) => {
-                        AppMessage::Action(Action::new(PaneAction::Open(id)))
-                    }
+        let handler = DocumentHandler {
+            text_content: Content::with_text(&content),
+            filename: get_file_name(&path),
+            path: Some(path),
+            changed: false,
+            edited_at: None,
+            saved_text: content.clone(),
+            format,
+            history: History::new(),
+            revision: 0,
+            metadata: Frontmatter::parse(&content),
+        };
 
-                    pane_stack::Message::ClosePane(id) => {
-                        AppMessage::Action(Action::new(PaneAction::Close(id)))
-                    }
+        self.state.documents.add(handler)
+    }
+
+    /// Keeps the closed pane to be reopened, with the state of its document
+    fn remember_closed(&mut self, pane: Pane, group: GroupId, index: usize) {
+        let document = match pane {
+            Pane::Empty | Pane::NewDocument => return,
+            Pane::Editor(id) => self
+                .state
+                .documents
+                .get_mut(&id)
+                .map(|handler| ClosedDocument {
+                    path: handler.path.clone(),
+                    filename: handler.filename.clone(),
+                    unsaved: (handler.changed || handler.path.is_none())
+                        .then(|| handler.text_content.text()),
+                    format: handler.format,
+                    cursor: cursor_offset(&handler.text_content),
+                    scroll: top_line(&mut handler.text_content),
+                }),
+            _ => None,
+        };
 
-                    pane_stack::Message::TextEditor(
-                        id,
-                        pane::text_editor::Message::EditorAction(action),
-                    ) => AppMessage::TextEditorAction(action, id),
+        self.state.closed.push(ClosedPane {
+            pane,
+            group,
+            index,
+            document,
+        });
+    }
 
-                    pane_stack::Message::None => AppMessage::None,
+    /// Restores the closed pane, reading its document again if it was closed too
+    fn reopen(&mut self, closed: ClosedPane) -> Task<AppMessage> {
+        let pane = match closed.pane {
+            Pane::Editor(id) | Pane::Backlinks(id) | Pane::Preview(id)
+                if self.state.documents.get(&id).is_some() =>
+            {
+                closed.pane
+            }
+            Pane::Editor(_) => {
+                let Some(document) = closed.document.clone() else {
+                    return Task::none();
+                };
+                let open = document
+                    .path
+                    .as_deref()
+                    .and_then(|path| self.state.documents.find_by_path(path));
+                match (open, document.path.clone()) {
+                    (Some(id), _) => Pane::Editor(id),
+                    (None, Some(path)) => {
+                        return Task::perform(open_file(path), move |result| {
+                            AppMessage::ReopenedFile(result, Box::new(closed.clone()))
+                        })
+                    }
+                    (None, None) => match self.restore_closed(document, None) {
+                        Some(id) => Pane::Editor(id),
+                        None => return Task::none(),
+                    },
                 }
+            }
+            // Backlinks and preview of a closed document have nothing to show
+            Pane::Backlinks(_) | Pane::Preview(_) => return Task::none(),
+            pane => pane,
+        };
+
+        self.insert_closed(pane, &closed);
+        Task::none()
+    }
+
+    /// Adds the document of a closed pane with its unsaved text, cursor and scroll
+    fn restore_closed(
+        &mut self,
+        document: ClosedDocument,
+        file: Option<(PathBuf, String, TextFormat)>,
+    ) -> Option<DocumentId> {
+        let id = match (file, document.path) {
+            (Some((path, text, format)), _) => self.add_document(path, text, format),
+            // Saving the unsaved text recreates the removed file
+            (None, Some(path)) if document.unsaved.is_some() => {
+                self.add_document(path, String::new(), document.format)
+            }
+            (None, Some(_)) => return None,
+            (None, None) => self.state.documents.add(DocumentHandler {
+                text_content: Content::new(),
+                path: None,
+                filename: document.filename,
+                format: document.format,
+                ..Default::default()
             }),
+        };
+
+        if let Some(handler) = self.state.documents.get_mut(&id) {
+            let current = handler.text_content.text();
+            if let Some(unsaved) = document.unsaved.filter(|unsaved| *unsaved != current) {
+                handler.history.record(Change::Other, || current.clone());
+                restore(handler, &current, unsaved);
+            }
+            let len = handler.text_content.text().len();
+            move_cursor(&mut handler.text_content, document.cursor.min(len));
+            // The cursor stays shown, as long as it was shown when the pane was closed
+            scroll_to_line(&mut handler.text_content, document.scroll);
+        }
+        Some(id)
+    }
+
+    /// Puts the reopened pane back at its place and opens it
+    fn insert_closed(&mut self, pane: Pane, closed: &ClosedPane) {
+        // Placeholder left after closing the last tab isn't needed anymore
+        let placeholder = self
+            .state
+            .panes
+            .group(&closed.group)
+            .and_then(|group| match group.tabs() {
+                [only] => Some(*only),
+                _ => None,
+            })
+            .filter(|only| self.state.panes.get(only) == Some(&Pane::NewDocument));
+
+        let id = self.state.panes.insert(pane, &closed.group, closed.index);
+        if let Some(placeholder) = placeholder {
+            self.state.panes.remove(&placeholder);
+        }
+        self.state.panes.open(&id);
+    }
+
+    /// Focuses the editor pane of the document, opening one if there is none
+    fn show_document(&mut self, id: DocumentId) {
+        let pane = Pane::Editor(id);
+        let existing = self
+            .state
+            .panes
+            .list()
+            .into_iter()
+            .find(|(_, open)| **open == pane)
+            .map(|(pane_id, _)| *pane_id);
+
+        // If opened pane is NewDocument, replace it with Editor pane
+        // otherwise add new one with Editor
+        if let Some(pane_id) = existing {
+            self.state.panes.open(&pane_id);
+        } else if let Some(&Pane::NewDocument) = self.state.panes.get_open() {
+            self.state.panes.replace(
+                &self.state.panes.get_open_id().cloned().unwrap_or(0usize),
+                pane,
+            );
+        } else {
+            let pane_id = self.state.panes.add(pane);
+            self.state.panes.open(&pane_id);
+        }
+    }
+
+    /// Flips the task item on the line of the open document
+    fn toggle_task(&mut self, document: DocumentId, line: usize) {
+        let Some(handler) = self.state.documents.get_mut(&document) else {
+            return;
+        };
+        let Some((marker, checked)) = task_at(handler, line) else {
+            return;
+        };
+
+        let cursor = cursor_offset(&handler.text_content);
+        select(&mut handler.text_content, marker..marker + 1);
+        let mark = if checked { " " } else { "x" };
+        perform(
+            handler,
+            text_editor::Action::Edit(Edit::Paste(Arc::new(String::from(mark)))),
         );
+        move_cursor(&mut handler.text_content, cursor);
+        handler.history.break_group();
+
+        if let Some(path) = &handler.path {
+            self.state.tasks.update(path, &handler.text_content.text());
+        }
+    }
+
+    fn find(&mut self, message: FindMessage, document: DocumentId) -> Task<AppMessage> {
+        let find = &mut self.state.find;
+
+        match message {
+            FindMessage::Open => {
+                find.visible = true;
+                return text_input::focus(find_input_id());
+            }
+            FindMessage::Close => find.visible = false,
+            FindMessage::Query(pattern) => {
+                find.query.pattern = pattern;
+                find.current = None;
+                find.counted = None;
+            }
+            FindMessage::Replacement(replacement) => find.replacement = replacement,
+            FindMessage::ToggleCaseSensitive => {
+                find.query.case_sensitive = !find.query.case_sensitive;
+                find.current = None;
+                find.counted = None;
+            }
+            FindMessage::ToggleWholeWord => {
+                find.query.whole_word = !find.query.whole_word;
+                find.current = None;
+                find.counted = None;
+            }
+            FindMessage::ToggleRegex => {
+                find.query.regex = !find.query.regex;
+                find.current = None;
+                find.counted = None;
+            }
+            FindMessage::Next | FindMessage::Previous | FindMessage::Replace => {
+                let Some(handler) = self.state.documents.get_mut(&document) else {
+                    return Task::none();
+                };
+                let Ok(searcher) = find.query.build() else {
+                    return Task::none();
+                };
+
+                let text = handler.text_content.text();
+                let mut matches = searcher.find_all(&text);
+
+                if let FindMessage::Replace = message {
+                    // Replace only when the selection is exactly the current match
+                    let current = find.current.and_then(|i| matches.get(i).cloned());
+                    if let Some(range) = current {
+                        if handler.text_content.selection().as_deref() == Some(&text[range.clone()])
+                        {
+                            let replacement = searcher.replacement(&text, range, &find.replacement);
+                            perform(
+                                handler,
+                                text_editor::Action::Edit(Edit::Paste(Arc::new(replacement))),
+                            );
+                            matches = searcher.find_all(&handler.text_content.text());
+                        }
+                    }
+                }
+
+                if matches.is_empty() {
+                    find.current = None;
+                    return Task::none();
+                }
+
+                let content = &mut handler.text_content;
+                let cursor = cursor_offset(content);
+                let current = if let FindMessage::Previous = message {
+                    // Cursor sits at the end of the selected match
+                    let selected = content.selection().map(|s| s.len()).unwrap_or(0);
+                    let start = cursor.saturating_sub(selected);
+                    matches
+                        .iter()
+                        .rposition(|range| range.start < start)
+                        .unwrap_or(matches.len() - 1)
+                } else {
+                    matches
+                        .iter()
+                        .position(|range| range.start >= cursor)
+                        .unwrap_or(0)
+                };
+
+                select(content, matches[current].clone());
+                handler.history.break_group();
+                find.current = Some(current);
+            }
+            FindMessage::ReplaceAll => {
+                let Some(handler) = self.state.documents.get_mut(&document) else {
+                    return Task::none();
+                };
+                let Ok(searcher) = find.query.build() else {
+                    return Task::none();
+                };
+
+                let content = &mut handler.text_content;
+                let (text, count) = searcher.replace_all(&content.text(), &find.replacement);
+                if count > 0 {
+                    // Content text always ends with a newline that is not part of the document
+                    let text = text.strip_suffix('\n').unwrap_or(&text).to_string();
+                    content.perform(text_editor::Action::SelectAll);
+                    perform(
+                        handler,
+                        text_editor::Action::Edit(Edit::Paste(Arc::new(text))),
+                    );
+                }
+                find.current = None;
+            }
+        }
+        Task::none()
+    }
+
+    fn view(&self) -> Element<AppMessage, Theme> {
+        let grid_elements = vec![pane_stack::pane_stack(&self.state).map(pane_message)];
         let grid = row(grid_elements);
 
         let primary_screen = Container::new(grid);
@@ -404,7 +1856,48 @@ impl App {
     }
 
     fn subscription(&self) -> Subscription<AppMessage> {
-        on_key_press(|key, modifiers| Some(AppMessage::OnKeyPress(key, modifiers)))
+        let keys = on_key_press(|key, modifiers| Some(AppMessage::OnKeyPress(key, modifiers)));
+
+        let autosave = match self.autosave() {
+            // Poll only while something waits to be saved
+            Autosave::AfterDelay(delay)
+                if self
+                    .state
+                    .documents
+                    .iter()
+                    .any(|(_, handler)| handler.edited_at.is_some()) =>
+            {
+                iced::time::every(delay / 4).map(|_| AppMessage::Autosave)
+            }
+            Autosave::OnFocusChange => iced::event::listen_with(|event, _, _| match event {
+                Event::Window(window::Event::Unfocused) => Some(AppMessage::Autosave),
+                _ => None,
+            }),
+            _ => Subscription::none(),
+        };
+
+        let journal = if self
+            .state
+            .documents
+            .iter()
+            .any(|(_, handler)| handler.changed)
+        {
+            iced::time::every(JOURNAL_INTERVAL).map(|_| AppMessage::Journal)
+        } else {
+            Subscription::none()
+        };
+
+        let mut paths: Vec<PathBuf> = self
+            .state
+            .documents
+            .iter()
+            .filter_map(|(_, handler)| handler.path.clone())
+            .collect();
+        paths.sort();
+        paths.dedup();
+        let files = watcher::watch(paths).map(AppMessage::FileEvent);
+
+        Subscription::batch([keys, autosave, journal, files])
     }
 
     fn on_key_press(
@@ -415,6 +1908,8 @@ impl App {
         if let Key::Character(c) = key {
             let modifier = if modifiers.control() && modifiers.alt() {
                 Modifiers::CtrlAlt
+            } else if modifiers.control() && modifiers.shift() {
+                Modifiers::CtrlShift
             } else if modifiers.control() {
                 Modifiers::Ctrl
             } else if modifiers.alt() {
@@ -424,7 +1919,7 @@ impl App {
             };
 
             let hotkey = HotKey {
-                key: c.chars().next().unwrap_or_default(),
+                key: c.chars().next().unwrap_or_default().to_ascii_lowercase(),
                 modifiers: modifier,
             };
 
@@ -436,6 +1931,234 @@ impl App {
     }
 }
 
+/// Moves the open tab by the offset among tabs of its group
+fn move_open_tab(state: &State, offset: isize) -> AppMessage {
+    let Some(&id) = state.panes.get_open_id() else {
+        return AppMessage::None;
+    };
+    let index = state
+        .panes
+        .group(&state.panes.focused())
+        .and_then(|group| group.tabs().iter().position(|&tab| tab == id));
+
+    match index.and_then(|index| index.checked_add_signed(offset)) {
+        Some(index) => AppMessage::Action(Action::new(PaneAction::Move { id, index })),
+        None => AppMessage::None,
+    }
+}
+
+/// Turns messages of the pane widgets into app messages
+fn pane_message(message: pane_stack::Message) -> AppMessage {
+    match message {
+        pane_stack::Message::NewDocument(pane::new_document::Message::PickFile) => {
+            AppMessage::Action(Action::new(FileAction::PickFile))
+        }
+        pane_stack::Message::NewDocument(pane::new_document::Message::NewFile) => {
+            AppMessage::Action(Action::new(FileAction::NewFile))
+        }
+
+        pane_stack::Message::NewPane(pane) => {
+            AppMessage::Action(Action::new(PaneAction::Add(pane)))
+        }
+
+        pane_stack::Message::OpenPane(id) => AppMessage::Action(Action::new(PaneAction::Open(id))),
+
+        pane_stack::Message::MovePane(id, index) => {
+            AppMessage::Action(Action::new(PaneAction::Move { id, index }))
+        }
+
+        pane_stack::Message::ClosePane(id) => {
+            AppMessage::Action(Action::new(PaneAction::Close(id)))
+        }
+
+        pane_stack::Message::TextEditor(id, pane::text_editor::Message::EditorAction(action)) => {
+            AppMessage::TextEditorAction(action, id)
+        }
+
+        pane_stack::Message::TextEditor(id, pane::text_editor::Message::Find(message)) => {
+            AppMessage::Find(message, id)
+        }
+
+        pane_stack::Message::TextEditor(id, pane::text_editor::Message::JumpToLine(line)) => {
+            AppMessage::JumpToLine(line, id)
+        }
+
+        pane_stack::Message::TextEditor(id, pane::text_editor::Message::SetFormat(format)) => {
+            AppMessage::Action(Action::new(DocumentAction::SetFormat(id, format)))
+        }
+
+        pane_stack::Message::Backlinks(pane::backlinks::Message::OpenNote(path)) => {
+            AppMessage::Action(Action::new(FileAction::OpenFileNewTab(path)))
+        }
+
+        pane_stack::Message::Preview(id, buffer::Message::ToggleTask(offset)) => {
+            AppMessage::TogglePreviewTask(id, offset)
+        }
+
+        pane_stack::Message::Tags(pane::tags::Message::SelectTag(tag)) => {
+            AppMessage::SelectTag(tag)
+        }
+
+        pane_stack::Message::Tags(pane::tags::Message::OpenNote(path)) => {
+            AppMessage::Action(Action::new(FileAction::OpenFileNewTab(path)))
+        }
+
+        pane_stack::Message::Tasks(pane::tasks::Message::Toggle(path, line)) => {
+            AppMessage::ToggleTaskAt(path, line)
+        }
+
+        pane_stack::Message::Tasks(pane::tasks::Message::Open(path, line)) => {
+            AppMessage::OpenAtLine(path, line)
+        }
+
+        pane_stack::Message::TextEditor(id, pane::text_editor::Message::Conflict(message)) => {
+            AppMessage::Conflict(message, id)
+        }
+
+        pane_stack::Message::Recovery(pane::recovery::Message::Restore(key)) => {
+            AppMessage::RestoreDocument(key)
+        }
+
+        pane_stack::Message::Recovery(pane::recovery::Message::Discard(key)) => {
+            AppMessage::DiscardRecovery(key)
+        }
+
+        pane_stack::Message::Focus(group, message) => {
+            AppMessage::FocusGroup(group, Box::new(pane_message(*message)))
+        }
+
+        pane_stack::Message::None => AppMessage::None,
+    }
+}
+
+/// Scans notes of the directory for links, tags and tasks in background
+fn index_vault(root: PathBuf) -> Task<AppMessage> {
+    Task::perform(
+        async move {
+            let mut links = LinkIndex::new(&root);
+            let mut tags = TagIndex::new();
+            let mut tasks = TaskIndex::new();
+            for (path, text) in read_notes(&root) {
+                links.update(&path, &text);
+                tags.update(&path, &text);
+                tasks.update(&path, &text);
+            }
+            (links, tags, tasks)
+        },
+        |(links, tags, tasks)| {
+            AppMessage::IndexedVault(Box::new(links), Box::new(tags), Box::new(tasks))
+        },
+    )
+}
+
+/// Finds the task checkbox on the line, returning its byte offset and whether it's checked
+fn task_at(handler: &DocumentHandler<Content>, line: usize) -> Option<(usize, bool)> {
+    let text = handler.text_content.text();
+    let start: usize = text.split_inclusive('\n').take(line).map(str::len).sum();
+    let end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+    markdown::task_marker(&text[start..end]).map(|(offset, checked)| (start + offset, checked))
+}
+
+/// Performs an editor action, recording edits in the document history
+fn perform(handler: &mut DocumentHandler<Content>, action: text_editor::Action) {
+    match &action {
+        text_editor::Action::Edit(edit) => {
+            let change = match edit {
+                Edit::Insert(_) => Change::Typing,
+                Edit::Backspace | Edit::Delete => Change::Deletion,
+                _ => Change::Other,
+            };
+            let content = &handler.text_content;
+            handler.history.record(change, || content.text());
+
+            // Typed words are undone one at a time
+            if let Edit::Insert(c) = edit {
+                if c.is_whitespace() {
+                    handler.history.break_group();
+                }
+            }
+
+            handler.text_content.perform(action);
+            handler.revision += 1;
+            handler.changed = !handler.history.is_saved();
+            handler.edited_at = handler.changed.then(Instant::now);
+
+            refresh(handler);
+        }
+        text_editor::Action::Scroll { .. } => handler.text_content.perform(action),
+        _ => {
+            // Moving the cursor starts a new undo step
+            handler.history.break_group();
+            handler.text_content.perform(action);
+        }
+    }
+}
+
+/// Updates data derived from the document text after an edit
+fn refresh(handler: &mut DocumentHandler<Content>) {
+    // Edits below a parsed block don't change it
+    let line = handler.text_content.cursor_position().0;
+    match leading_block(&handler.text_content) {
+        Some((block, last)) if handler.metadata.is_none() || line <= last => {
+            handler.metadata = Frontmatter::parse(&block);
+        }
+        Some(_) => {}
+        None => handler.metadata = None,
+    }
+}
+
+/// Delimited block at the start of the document and its last line,
+/// read line by line so the rest of the text isn't copied
+fn leading_block(content: &Content) -> Option<(String, usize)> {
+    let delimiter = content.line(0)?.trim_end().to_string();
+    if delimiter != "---" && delimiter != "+++" {
+        return None;
+    }
+
+    let mut block = format!("{delimiter}\n");
+    for i in 1..content.line_count() {
+        let line = content.line(i)?;
+        block.push_str(&line);
+        block.push('\n');
+        if line.trim_end() == delimiter {
+            return Some((block, i));
+        }
+    }
+    None
+}
+
+/// Replaces document text with the file text, keeping the old one in the history
+fn reload(handler: &mut DocumentHandler<Content>, text: String, format: TextFormat) {
+    let current = handler.text_content.text();
+    handler.history.record(Change::Other, || current.clone());
+    handler.history.break_group();
+    restore(handler, &current, text.clone());
+
+    handler.history.mark_saved();
+    handler.changed = false;
+    handler.edited_at = None;
+    handler.saved_text = text;
+    handler.format = format;
+}
+
+/// Replaces document text with a snapshot from the history
+fn restore(handler: &mut DocumentHandler<Content>, current: &str, text: String) {
+    // Place the cursor where the texts start to differ
+    let offset = current
+        .char_indices()
+        .zip(text.chars())
+        .find(|((_, a), b)| a != b)
+        .map(|((i, _), _)| i)
+        .unwrap_or(current.len().min(text.len()));
+
+    handler.text_content = Content::with_text(&text);
+    handler.revision += 1;
+    move_cursor(&mut handler.text_content, offset.min(text.len()));
+    handler.changed = !handler.history.is_saved();
+    handler.edited_at = handler.changed.then(Instant::now);
+    handler.metadata = Frontmatter::parse(&text);
+}
+
 fn main() -> iced::Result {
     env_logger::init();
 
@@ -481,6 +2204,11 @@ fn main() -> iced::Result {
         "theme",
         Value::String(SmolStr::new(DEFAULT_THEME)),
     );
+    // Number of previous versions kept when saving, 0 disables backups
+    default_config.insert("system", "backups", Value::Integer(0));
+    // "off", "after_delay" or "on_focus_change"
+    default_config.insert("editor", "autosave", Value::String(SmolStr::new("off")));
+    default_config.insert("editor", "autosave_delay_ms", Value::Integer(1000));
 
     // Loading system config from file or initializing it with default one
     let system_config =
@@ -497,3 +2225,100 @@ fn main() -> iced::Result {
         .centered()
         .run_with(move || App::new(config))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn autosave_of_closed_document() {
+        let dir = std::env::temp_dir().join(format!("strelka-close-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let note = dir.join("note.md");
+        std::fs::write(&note, "saved\n").unwrap();
+
+        let mut config = Config::new();
+        let dir_value = Value::String(SmolStr::new(dir.to_str().unwrap()));
+        config.insert("system", "config_dir", dir_value);
+        config.insert(
+            "editor",
+            "autosave",
+            Value::String(SmolStr::new("after_delay")),
+        );
+        let (mut app, _) = App::new(config);
+
+        let id = app.open_document(note.clone(), String::from("saved\n"), TextFormat::default());
+        let text = String::from("- [ ] edited\n");
+        if let Some(handler) = app.state.documents.get_mut(&id) {
+            handler.text_content = Content::with_text(&text);
+            handler.changed = true;
+        }
+        app.journal
+            .write(&recovery::Key::File(note.clone()), &text)
+            .unwrap();
+
+        let pane = app.state.panes.get_open_id().copied().unwrap();
+        let _ = app.update(AppMessage::Action(Action::new(PaneAction::Close(pane))));
+        assert!(app.state.documents.get(&id).is_none());
+
+        // Save started by closing reports back after the document is gone
+        let _ = app.update(AppMessage::SavedFile(id, note.clone(), Arc::new(text)));
+        assert!(app.journal.entries().is_empty());
+        assert_eq!(app.state.tasks.open().len(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn typed_frontmatter() {
+        let mut handler = DocumentHandler {
+            text_content: Content::with_text("# Body"),
+            ..Default::default()
+        };
+        for line in ["---", "title: Typed", "---"] {
+            for c in line.chars() {
+                perform(&mut handler, text_editor::Action::Edit(Edit::Insert(c)));
+            }
+            perform(&mut handler, text_editor::Action::Edit(Edit::Enter));
+        }
+        assert_eq!(handler.title(), Some("Typed"));
+
+        // Breaking the closing delimiter drops the metadata
+        perform(&mut handler, text_editor::Action::Edit(Edit::Backspace));
+        perform(&mut handler, text_editor::Action::Edit(Edit::Backspace));
+        assert_eq!(handler.title(), None);
+    }
+
+    #[test]
+    fn reopen_closed_position() {
+        let dir = std::env::temp_dir().join(format!("strelka-reopen-{}", std::process::id()));
+        let mut config = Config::new();
+        let dir_value = Value::String(SmolStr::new(dir.to_str().unwrap()));
+        config.insert("system", "config_dir", dir_value);
+        let (mut app, _) = App::new(config);
+
+        let text: String = (0..100).map(|i| format!("line {i}\n")).collect();
+        let id = app.state.documents.add(DocumentHandler {
+            text_content: Content::with_text(&text),
+            ..Default::default()
+        });
+        let pane = app.state.panes.add(Pane::Editor(id));
+        app.state.panes.open(&pane);
+        if let Some(handler) = app.state.documents.get_mut(&id) {
+            move_cursor(&mut handler.text_content, 400);
+            scroll_to_line(&mut handler.text_content, 40);
+        }
+
+        let _ = app.update(AppMessage::Action(Action::new(PaneAction::Close(pane))));
+        let _ = app.update(AppMessage::Action(Action::new(PaneAction::ReopenClosed)));
+
+        let Some(&Pane::Editor(id)) = app.state.panes.get_open() else {
+            panic!("editor isn't reopened");
+        };
+        let handler = app.state.documents.get_mut(&id).unwrap();
+        assert_eq!(cursor_offset(&handler.text_content), 400);
+        assert_eq!(top_line(&mut handler.text_content), 40);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
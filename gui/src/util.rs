@@ -1,26 +1,156 @@
+use core::encoding::{self, TextFormat};
 use std::{
-    path::{Path, PathBuf},
+    ffi::OsString,
+    io,
+    path::{Component, Path, PathBuf},
     sync::Arc,
 };
 
 use tokio::{fs, io::AsyncWriteExt};
 
+/// Previous versions kept when a file is overwritten
+#[derive(Debug, Default, Clone)]
+pub struct Backups {
+    /// Number of kept copies, 0 disables backups
+    pub count: usize,
+    /// Directory relative to the workdir, copies are kept next to the file if it's not set
+    pub directory: Option<PathBuf>,
+    pub workdir: PathBuf,
+}
+
 pub async fn save_file(path: PathBuf, text: Arc<String>) -> tokio::io::Result<()> {
-    let mut file = fs::File::create(path).await?;
+    write_atomic(&path, text.as_bytes()).await
+}
+
+/// Writes the text in encoding and line endings of the format, backing up the old file
+pub async fn save_document(
+    path: PathBuf,
+    text: Arc<String>,
+    format: TextFormat,
+    backups: Backups,
+) -> tokio::io::Result<()> {
+    let bytes = encoding::encode(&text, &format)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+    // Losing a backup is better than losing the edits
+    if let Err(err) = backup(&path, &backups).await {
+        log::warn!("Can't back up {path:?}: {err}");
+    }
+    write_atomic(&path, &bytes).await
+}
+
+/// Writes into a temporary file next to the target and renames it over the target,
+/// so the file is never left half written
+async fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    // Replace the file a symlink points to rather than the link itself
+    let path = fs::canonicalize(path)
+        .await
+        .unwrap_or_else(|_| path.to_path_buf());
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+
+    // Hidden, so the workdir scan never picks it up
+    let mut temp_name = OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(".tmp");
+    let temp = path.with_file_name(temp_name);
+
+    let permissions = fs::metadata(&path)
+        .await
+        .ok()
+        .map(|meta| meta.permissions());
+
+    let result = async {
+        let mut file = fs::File::create(&temp).await?;
+        file.write_all(bytes).await?;
+        file.sync_all().await?;
+        drop(file);
+
+        if let Some(permissions) = permissions {
+            fs::set_permissions(&temp, permissions).await?;
+        }
+        fs::rename(&temp, &path).await
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp).await;
+        return result;
+    }
+
+    // Persist the rename itself
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        if let Ok(directory) = fs::File::open(parent).await {
+            let _ = directory.sync_all().await;
+        }
+    }
 
-    file.write_all(text.as_bytes()).await?;
+    Ok(())
+}
+
+/// Copies the file to a backup, shifting older backups.
+/// Single backup is named `note.md.bak`, a set is named `note.md.1.bak`, `note.md.2.bak` and so on.
+async fn backup(path: &Path, backups: &Backups) -> io::Result<()> {
+    if backups.count == 0 || !fs::try_exists(path).await? {
+        return Ok(());
+    }
+
+    let path = fs::canonicalize(path).await?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let parent = path.parent().unwrap_or(Path::new(""));
+
+    let directory = match &backups.directory {
+        Some(directory) => {
+            let workdir = fs::canonicalize(&backups.workdir)
+                .await
+                .unwrap_or_else(|_| backups.workdir.clone());
+            // Files outside the workdir are kept by their absolute path
+            let relative: PathBuf = match parent.strip_prefix(&workdir) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => parent
+                    .components()
+                    .filter(|component| matches!(component, Component::Normal(_)))
+                    .collect(),
+            };
+            workdir.join(directory).join(relative)
+        }
+        None => parent.to_path_buf(),
+    };
+    fs::create_dir_all(&directory).await?;
+
+    let backup_path = |n: usize| {
+        let mut backup = name.to_os_string();
+        if backups.count > 1 {
+            backup.push(format!(".{n}"));
+        }
+        backup.push(".bak");
+        directory.join(backup)
+    };
+
+    for n in (1..backups.count).rev() {
+        let older = backup_path(n);
+        if fs::try_exists(&older).await? {
+            fs::rename(&older, backup_path(n + 1)).await?;
+        }
+    }
+    fs::copy(&path, backup_path(1)).await?;
 
-    file.flush().await?;
     Ok(())
 }
 
-pub async fn open_file(path: impl Into<PathBuf>) -> Result<(PathBuf, String), ()> {
+/// Reads the file, detecting its encoding and line endings
+pub async fn open_file(path: impl Into<PathBuf>) -> Result<(PathBuf, String, TextFormat), ()> {
     let path = path.into();
-    let content = fs::read_to_string(&path).await.map_err(|_| ())?;
-    Ok((path, content))
+    let bytes = fs::read(&path).await.map_err(|_| ())?;
+    let (content, format) = encoding::decode(&bytes);
+    Ok((path, content, format))
 }
 
-pub async fn pick_file(directory: Option<PathBuf>) -> Result<(PathBuf, String), ()> {
+pub async fn pick_file(directory: Option<PathBuf>) -> Result<(PathBuf, String, TextFormat), ()> {
     let handler = if let Some(dir) = directory {
         rfd::AsyncFileDialog::new().set_directory(dir)
     } else {
@@ -37,6 +167,47 @@ pub async fn pick_file(directory: Option<PathBuf>) -> Result<(PathBuf, String),
     }
 }
 
+/// Asks where to save a document, starting next to its current file if it has one
+pub async fn pick_save_path(current: Option<PathBuf>, name: String) -> Option<PathBuf> {
+    let mut dialog = rfd::AsyncFileDialog::new().set_file_name(name);
+    if let Some(directory) = current.as_deref().and_then(Path::parent) {
+        dialog = dialog.set_directory(directory);
+    }
+
+    let handler = dialog.save_file().await?;
+    Some(handler.path().to_path_buf())
+}
+
+/// Asks where to save exported HTML and writes it there, `None` if the dialog was cancelled
+pub async fn export_html(name: String, html: Arc<String>) -> io::Result<Option<PathBuf>> {
+    let Some(handler) = rfd::AsyncFileDialog::new()
+        .set_file_name(name)
+        .add_filter("HTML", &["html"])
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+
+    let path = handler.path().to_path_buf();
+    save_file(path.clone(), html).await?;
+    Ok(Some(path))
+}
+
+/// Flips the task checkbox on the line of the file, returning its new text
+pub async fn toggle_task_in_file(
+    path: PathBuf,
+    line: usize,
+    backups: Backups,
+) -> Result<(PathBuf, String), ()> {
+    let (path, text, format) = open_file(path).await?;
+    let text = core::vault::tasks::toggle(&text, line).ok_or(())?;
+    save_document(path.clone(), Arc::new(text.clone()), format, backups)
+        .await
+        .map_err(|_| ())?;
+    Ok((path, text))
+}
+
 pub fn get_file_name(path: &Path) -> String {
     path.file_name()
         .and_then(|os_str| os_str.to_str())
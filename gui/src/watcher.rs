@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use iced::{futures::channel::mpsc, stream, Subscription};
+use notify::{
+    event::{ModifyKind, RenameMode},
+    Event, EventKind, RecursiveMode, Watcher,
+};
+
+/// Change of a watched file on disk
+#[derive(Debug, Clone)]
+pub enum FileEvent {
+    Changed(PathBuf),
+    Removed(PathBuf),
+    /// File was moved from the first path to the second one
+    Renamed(PathBuf, PathBuf),
+}
+
+/// Watches the files for changes made by other programs.
+/// Parent directories are watched, so replacing a file by rename is noticed too.
+pub fn watch(paths: Vec<PathBuf>) -> Subscription<FileEvent> {
+    let id = paths.clone();
+    Subscription::run_with_id(
+        id,
+        stream::channel(32, move |output: mpsc::Sender<FileEvent>| async move {
+            let mut sender = output.clone();
+            let watched = paths.clone();
+            let handler = move |result: notify::Result<Event>| {
+                if let Ok(event) = result {
+                    for event in file_events(event, &watched) {
+                        let _ = sender.try_send(event);
+                    }
+                }
+            };
+
+            let mut watcher = match notify::recommended_watcher(handler) {
+                Ok(watcher) => watcher,
+                Err(err) => {
+                    log::warn!("Can't watch files: {err}");
+                    return;
+                }
+            };
+
+            let mut directories: Vec<&Path> = paths.iter().filter_map(|p| p.parent()).collect();
+            directories.sort();
+            directories.dedup();
+            for directory in directories {
+                if let Err(err) = watcher.watch(directory, RecursiveMode::NonRecursive) {
+                    log::warn!("Can't watch {directory:?}: {err}");
+                }
+            }
+
+            // The watcher stops when dropped, so keep it until the subscription ends
+            std::future::pending::<()>().await;
+            drop(watcher);
+        }),
+    )
+}
+
+/// Converts the event into changes of watched files
+fn file_events(event: Event, watched: &[PathBuf]) -> Vec<FileEvent> {
+    let is_watched = |path: &PathBuf| watched.contains(path);
+
+    match event.kind {
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            let [from, to]: [PathBuf; 2] = match event.paths.try_into() {
+                Ok(paths) => paths,
+                Err(_) => return Vec::new(),
+            };
+            if is_watched(&from) || is_watched(&to) {
+                vec![FileEvent::Renamed(from, to)]
+            } else {
+                Vec::new()
+            }
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) | EventKind::Remove(_) => event
+            .paths
+            .into_iter()
+            .filter(is_watched)
+            .map(FileEvent::Removed)
+            .collect(),
+        EventKind::Create(_) | EventKind::Modify(_) => event
+            .paths
+            .into_iter()
+            .filter(is_watched)
+            .map(FileEvent::Changed)
+            .collect(),
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => Vec::new(),
+    }
+}
@@ -1,7 +1,19 @@
 use config::Config;
-use core::{document::DocumentStore, pane::PaneModel, smol_str::SmolStr, value::Value};
+use core::{
+    buffer::{Buffer, FormattedBuffer},
+    diff::Diff,
+    document::{DocumentId, DocumentStore},
+    markdown::OutlineEntry,
+    pane::{ClosedPanes, PaneModel},
+    recovery,
+    search::SearchQuery,
+    smol_str::SmolStr,
+    value::Value,
+    vault::{links::LinkIndex, tags::TagIndex, tasks::TaskIndex},
+};
 use iced::widget::text_editor::Content;
 use log::info;
+use std::collections::HashMap;
 use theming::{
     catalog::{Catalog, ThemeID},
     Theme,
@@ -10,8 +22,58 @@ use theming::{
 pub struct State {
     pub documents: DocumentStore<Content>,
     pub panes: PaneModel,
+    /// Recently closed panes that can be reopened
+    pub closed: ClosedPanes,
     pub themes: Catalog,
     pub config: Config,
+    pub find: FindBar,
+    /// Whether the outline sidebar is shown next to the editor
+    pub show_outline: bool,
+    /// Wiki links between notes of the workdir
+    pub links: LinkIndex,
+    /// Tags of the workdir notes
+    pub tags: TagIndex,
+    /// Task list items of the workdir notes
+    pub tasks: TaskIndex,
+    /// Journaled documents that can be restored
+    pub recovery: Vec<recovery::Entry>,
+    /// Changes on disk clashing with unsaved edits
+    pub conflicts: HashMap<DocumentId, Conflict>,
+    /// Last save or export error of documents, cleared when they're saved
+    pub save_errors: HashMap<DocumentId, String>,
+    /// Tag which notes are listed in the tags pane
+    pub selected_tag: Option<String>,
+    /// Documents rendered in preview panes with the revision and text they were parsed at
+    pub previews: HashMap<DocumentId, (usize, Buffer, FormattedBuffer)>,
+    /// Headings of documents in shown editors while the outline sidebar is on,
+    /// with the revision they were listed at
+    pub outlines: HashMap<DocumentId, (usize, Vec<OutlineEntry>)>,
+}
+
+/// Change of a document file made by another program
+#[derive(Debug, Clone)]
+pub enum Conflict {
+    /// File has new text while the document has unsaved edits
+    Changed {
+        theirs: String,
+        /// Diff against the document, computed when it's shown
+        diff: Option<Diff>,
+    },
+    Deleted,
+}
+
+/// Find and replace bar of the open editor
+#[derive(Debug, Default)]
+pub struct FindBar {
+    pub visible: bool,
+    pub query: SearchQuery,
+    pub replacement: String,
+    /// Index of the selected match
+    pub current: Option<usize>,
+    /// Number of matches, `None` if the pattern is invalid
+    pub matches: Option<usize>,
+    /// Document and its revision the matches were counted in
+    pub counted: Option<(DocumentId, usize)>,
 }
 
 impl State {
@@ -0,0 +1,87 @@
+use std::fmt::Write;
+
+use core::highlight::Token;
+
+use crate::{Color, Theme};
+
+impl Theme {
+    /// Builds a stylesheet for exported documents from the markdown and syntax styles
+    pub fn to_css(&self) -> String {
+        let markdown = &self.markdown;
+        let mut css = String::new();
+
+        let _ = writeln!(
+            css,
+            "body {{ max-width: {}px; margin: 0 auto; padding: 32px; font-family: sans-serif; \
+             font-size: {}px; color: {}; background: {}; }}",
+            self.editor.width,
+            markdown.text_size,
+            rgba(markdown.text),
+            rgba(self.editor.background2),
+        );
+        let _ = writeln!(
+            css,
+            "body > * + * {{ margin-top: {}px; }}",
+            markdown.spacing
+        );
+
+        for (i, heading) in markdown.headings.iter().enumerate() {
+            let _ = writeln!(
+                css,
+                "h{} {{ font-size: {}px; color: {}; }}",
+                i + 1,
+                heading.size,
+                rgba(heading.color)
+            );
+        }
+
+        let _ = writeln!(css, "a {{ color: {}; }}", rgba(markdown.link));
+        let _ = writeln!(css, "strong {{ color: {}; }}", rgba(markdown.strong));
+        let _ = writeln!(css, "em {{ color: {}; }}", rgba(markdown.emphasis));
+        let _ = writeln!(css, "del {{ color: {}; }}", rgba(markdown.strikethrough));
+        let _ = writeln!(
+            css,
+            "code {{ font-family: monospace; color: {}; background: {}; }}",
+            rgba(markdown.code),
+            rgba(markdown.code_background)
+        );
+        let _ = writeln!(
+            css,
+            "pre {{ padding: 8px; background: {}; overflow-x: auto; }}",
+            rgba(markdown.code_background)
+        );
+        let _ = writeln!(
+            css,
+            "blockquote {{ margin-left: 0; padding-left: 16px; color: {}; \
+             border-left: 3px solid {}; }}",
+            rgba(markdown.quote),
+            rgba(markdown.quote_bar)
+        );
+        let _ = writeln!(
+            css,
+            "hr {{ border: none; border-top: 1px solid {}; }}",
+            rgba(markdown.rule)
+        );
+        css.push_str("li.task { list-style: none; }\n");
+        css.push_str("table { border-collapse: collapse; }\n");
+        css.push_str("th, td { padding: 4px 8px; }\n");
+
+        for token in Token::ALL {
+            if let Some(color) = self.syntax.color(token) {
+                let _ = writeln!(css, ".tok-{} {{ color: {}; }}", token.name(), rgba(color));
+            }
+        }
+
+        css
+    }
+}
+
+fn rgba(color: Color) -> String {
+    format!(
+        "rgba({}, {}, {}, {})",
+        (color.r * 255.0).round() as u8,
+        (color.g * 255.0).round() as u8,
+        (color.b * 255.0).round() as u8,
+        color.a
+    )
+}
@@ -37,3 +37,25 @@ pub fn background2(theme: &Theme) -> Style {
         ..Default::default()
     }
 }
+
+pub fn code_block(theme: &Theme) -> Style {
+    Style {
+        text_color: Some(theme.markdown.code.into()),
+        background: Some(theme.markdown.code_background.into()),
+        ..Default::default()
+    }
+}
+
+pub fn quote_bar(theme: &Theme) -> Style {
+    Style {
+        background: Some(theme.markdown.quote_bar.into()),
+        ..Default::default()
+    }
+}
+
+pub fn rule(theme: &Theme) -> Style {
+    Style {
+        background: Some(theme.markdown.rule.into()),
+        ..Default::default()
+    }
+}
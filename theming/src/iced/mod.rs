@@ -4,3 +4,4 @@ pub mod scrollable;
 pub mod svg;
 pub mod text;
 pub mod text_editor;
+pub mod text_input;
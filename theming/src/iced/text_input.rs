@@ -0,0 +1,39 @@
+use iced_core::Border;
+use iced_widget::text_input::{Catalog, Status, Style, StyleFn};
+
+use crate::Theme;
+
+impl Catalog for Theme {
+    type Class<'a> = StyleFn<'a, Theme>;
+
+    fn default<'a>() -> Self::Class<'a> {
+        Box::new(default)
+    }
+
+    fn style(&self, class: &Self::Class<'_>, status: Status) -> Style {
+        class(self, status)
+    }
+}
+
+pub fn default(theme: &Theme, status: Status) -> Style {
+    let border = match status {
+        Status::Focused => theme.primary_button.active.background,
+        _ => theme.generic.background2,
+    };
+
+    let mut placeholder = theme.generic.text;
+    placeholder.a *= 0.5;
+
+    Style {
+        background: theme.generic.background.into(),
+        border: Border {
+            color: border.into(),
+            width: 1.0,
+            radius: 4.0.into(),
+        },
+        icon: theme.generic.text.into(),
+        placeholder: placeholder.into(),
+        value: theme.generic.text.into(),
+        selection: theme.editor.selection.into(),
+    }
+}
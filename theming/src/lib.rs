@@ -1,12 +1,13 @@
 mod border;
 pub mod catalog;
+mod css;
 mod font;
 #[cfg(feature = "iced")]
 pub mod iced;
 mod margin;
 pub mod metadata;
 mod padding;
-mod styles;
+pub mod styles;
 mod theme;
 
 use std::sync::{Arc, RwLock};
@@ -2,7 +2,10 @@ use crate::Color;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Fields missing from a theme, like the markdown syntax colors that older
+/// themes don't have, are taken from the fallback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Editor {
     pub background: Color,
     pub background2: Color,
@@ -12,6 +15,17 @@ pub struct Editor {
     pub radius: f32,
     pub padding: f32,
     pub width: f32,
+
+    // Markdown syntax
+    pub heading: Color,
+    pub marker: Color,
+    pub strong: Color,
+    pub emphasis: Color,
+    pub strikethrough: Color,
+    pub code: Color,
+    pub link: Color,
+    pub list_marker: Color,
+    pub quote: Color,
 }
 
 impl Editor {
@@ -24,5 +38,20 @@ impl Editor {
         radius: 0.0,
         padding: 0.0,
         width: 500.0,
+        heading: Color::new(0.0, 0.0, 0.0, 1.0),
+        marker: Color::new(0.6, 0.6, 0.6, 1.0),
+        strong: Color::new(0.0, 0.0, 0.0, 1.0),
+        emphasis: Color::new(0.0, 0.0, 0.0, 1.0),
+        strikethrough: Color::new(0.5, 0.5, 0.5, 1.0),
+        code: Color::new(0.6, 0.2, 0.2, 1.0),
+        link: Color::new(0.2, 0.4, 0.8, 1.0),
+        list_marker: Color::new(0.4, 0.4, 0.4, 1.0),
+        quote: Color::new(0.4, 0.4, 0.4, 1.0),
     };
 }
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self::FALLBACK
+    }
+}
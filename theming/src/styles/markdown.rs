@@ -0,0 +1,69 @@
+use crate::Color;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Markdown {
+    pub text: Color,
+    pub text_size: f32,
+    pub spacing: f32,
+    /// Heading styles from level 1 to 6
+    pub headings: [Heading; 6],
+    pub link: Color,
+    pub strong: Color,
+    pub emphasis: Color,
+    pub strikethrough: Color,
+    pub code: Color,
+    pub code_background: Color,
+    pub quote: Color,
+    pub quote_bar: Color,
+    pub rule: Color,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Heading {
+    pub size: f32,
+    pub color: Color,
+}
+
+impl Markdown {
+    pub const FALLBACK: Markdown = Markdown {
+        text: Color::BLACK,
+        text_size: 16.0,
+        spacing: 8.0,
+        headings: [
+            Heading::new(32.0, Color::BLACK),
+            Heading::new(28.0, Color::BLACK),
+            Heading::new(24.0, Color::BLACK),
+            Heading::new(20.0, Color::BLACK),
+            Heading::new(18.0, Color::BLACK),
+            Heading::new(16.0, Color::BLACK),
+        ],
+        link: Color::new(0.2, 0.4, 0.8, 1.0),
+        strong: Color::BLACK,
+        emphasis: Color::BLACK,
+        strikethrough: Color::new(0.5, 0.5, 0.5, 1.0),
+        code: Color::BLACK,
+        code_background: Color::new(0.9, 0.9, 0.9, 1.0),
+        quote: Color::new(0.3, 0.3, 0.3, 1.0),
+        quote_bar: Color::new(0.8, 0.8, 0.8, 1.0),
+        rule: Color::new(0.8, 0.8, 0.8, 1.0),
+    };
+
+    /// Returns style of the heading with given level, clamped to 1..=6
+    pub fn heading(&self, level: u8) -> Heading {
+        self.headings[level.clamp(1, 6) as usize - 1]
+    }
+}
+
+impl Default for Markdown {
+    fn default() -> Self {
+        Self::FALLBACK
+    }
+}
+
+impl Heading {
+    pub const fn new(size: f32, color: Color) -> Self {
+        Self { size, color }
+    }
+}
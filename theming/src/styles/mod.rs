@@ -5,7 +5,9 @@ pub mod file_explorer;
 pub mod generic;
 pub mod list;
 pub mod list_item;
+pub mod markdown;
 pub mod notification;
 pub mod notification_list;
+pub mod syntax;
 pub mod tab;
 pub mod tab_bar;
@@ -0,0 +1,54 @@
+use core::highlight::Token;
+
+use crate::Color;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Syntax {
+    pub comment: Color,
+    pub keyword: Color,
+    pub string: Color,
+    pub number: Color,
+    pub constant: Color,
+    pub r#type: Color,
+    pub function: Color,
+    pub operator: Color,
+    pub variable: Color,
+}
+
+impl Syntax {
+    pub const FALLBACK: Syntax = Syntax {
+        comment: Color::new(0.5, 0.5, 0.5, 1.0),
+        keyword: Color::new(0.6, 0.2, 0.6, 1.0),
+        string: Color::new(0.3, 0.6, 0.3, 1.0),
+        number: Color::new(0.6, 0.4, 0.0, 1.0),
+        constant: Color::new(0.6, 0.4, 0.0, 1.0),
+        r#type: Color::new(0.7, 0.5, 0.0, 1.0),
+        function: Color::new(0.2, 0.4, 0.9, 1.0),
+        operator: Color::new(0.0, 0.5, 0.7, 1.0),
+        variable: Color::new(0.9, 0.3, 0.3, 1.0),
+    };
+
+    /// Returns color of the token, `None` for plain text
+    pub fn color(&self, token: Token) -> Option<Color> {
+        match token {
+            Token::Plain => None,
+            Token::Comment => Some(self.comment),
+            Token::Keyword => Some(self.keyword),
+            Token::String => Some(self.string),
+            Token::Number => Some(self.number),
+            Token::Constant => Some(self.constant),
+            Token::Type => Some(self.r#type),
+            Token::Function => Some(self.function),
+            Token::Operator => Some(self.operator),
+            Token::Variable => Some(self.variable),
+        }
+    }
+}
+
+impl Default for Syntax {
+    fn default() -> Self {
+        Self::FALLBACK
+    }
+}
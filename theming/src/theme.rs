@@ -2,8 +2,9 @@ use std::{borrow::Cow, path::PathBuf};
 
 use crate::styles::{
     button::Button, context_menu::ContextMenu, editor::Editor, file_explorer::FileExplorer,
-    generic::Generic, list::List, list_item::ListItem, notification::Notification,
-    notification_list::NotificationList, tab::Tab, tab_bar::TabBar,
+    generic::Generic, list::List, list_item::ListItem, markdown::Markdown,
+    notification::Notification, notification_list::NotificationList, syntax::Syntax, tab::Tab,
+    tab_bar::TabBar,
 };
 
 #[cfg(feature = "iced")]
@@ -21,6 +22,8 @@ pub const FALLBACK: Theme = Theme {
     list_item: ListItem::FALLBACK,
     list: List::FALLBACK,
     editor: Editor::FALLBACK,
+    markdown: Markdown::FALLBACK,
+    syntax: Syntax::FALLBACK,
     notification: Notification::FALLBACK,
     notification_list: NotificationList::FALLBACK,
     context_menu: ContextMenu::FALLBACK,
@@ -49,6 +52,11 @@ pub struct Theme {
     pub notification_list: NotificationList,
 
     pub editor: Editor,
+    // Themes written before these sections existed get the fallback ones
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub markdown: Markdown,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub syntax: Syntax,
     pub context_menu: ContextMenu,
     pub file_explorer: FileExplorer,
     pub generic: Generic,
@@ -86,3 +94,44 @@ pub struct Info<'a> {
     pub name: Cow<'a, str>,
     pub description: Cow<'a, str>,
 }
+
+#[cfg(all(test, feature = "load"))]
+mod tests {
+    use super::*;
+
+    const LIGHT: &str = include_str!("../../themes/light/theme.toml");
+
+    /// Removes the section with its subsections from theme text
+    fn without_section(text: &str, section: &str) -> String {
+        let start = text.find(&format!("\n[{section}]")).unwrap();
+        let end = text[start + 1..]
+            .find("\n[")
+            .map_or(text.len(), |end| start + 1 + end);
+        format!("{}{}", &text[..start], &text[end..])
+    }
+
+    #[test]
+    fn missing_markdown_section() {
+        let theme: Theme = toml::from_str(&without_section(LIGHT, "markdown")).unwrap();
+        assert_eq!(theme.markdown.text, Markdown::FALLBACK.text);
+        assert_eq!(theme.markdown.text_size, Markdown::FALLBACK.text_size);
+    }
+
+    #[test]
+    fn missing_editor_markdown_colors() {
+        let start = LIGHT.find("heading = ").unwrap();
+        let end = start + LIGHT[start..].find("\n\n").unwrap();
+        let text = format!("{}{}", &LIGHT[..start], &LIGHT[end..]);
+
+        let theme: Theme = toml::from_str(&text).unwrap();
+        assert_eq!(theme.editor.heading, Editor::FALLBACK.heading);
+        assert_eq!(theme.editor.quote, Editor::FALLBACK.quote);
+        assert_ne!(theme.editor.background2, Editor::FALLBACK.background2);
+    }
+
+    #[test]
+    fn missing_syntax_section() {
+        let theme: Theme = toml::from_str(&without_section(LIGHT, "syntax")).unwrap();
+        assert_eq!(theme.syntax.keyword, Syntax::FALLBACK.keyword);
+    }
+}
@@ -1,22 +1,273 @@
-use core::buffer::FormattedBuffer;
+use core::{
+    buffer::{
+        Element, ElementKind, FormattedBuffer, Inline, InlineKind, SpanAttributes, TableCell,
+    },
+    highlight::{self, Tokens},
+};
 
-use iced::widget::{text, Column};
-use theming::Theme;
+use iced::{
+    font::{Family, Style, Weight},
+    widget::{container, rich_text, row, span, stack, text, Column, Row, Space},
+    Font, Length, Padding,
+};
+use theming::{styles::markdown::Markdown, theme, Color, Theme};
 
-pub fn buffer<'a, Message: Clone + 'a>(
-    buffer: &'a FormattedBuffer,
+use crate::button::text_button;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Flips the task item starting at the byte offset of the source
+    ToggleTask(usize),
+}
+
+/// Renders formatted buffer using markdown styles of the active theme
+pub fn buffer<'a>(buffer: &'a FormattedBuffer) -> iced::Element<'a, Message, Theme> {
+    let style = theme!(markdown);
+    blocks(&buffer.elements, &style)
+}
+
+fn blocks<'a>(elements: &'a [Element], style: &Markdown) -> iced::Element<'a, Message, Theme> {
+    Column::with_children(elements.iter().map(|child| element(child, style)))
+        .spacing(style.spacing)
+        .into()
+}
+
+fn element<'a>(element: &'a Element, style: &Markdown) -> iced::Element<'a, Message, Theme> {
+    match &element.kind {
+        ElementKind::Heading { level, content } => {
+            let heading = style.heading(*level);
+            let paint = Paint {
+                size: heading.size,
+                color: heading.color,
+                bold: true,
+            };
+            inlines(content, style, paint)
+        }
+        ElementKind::Paragraph(content) => inlines(content, style, Paint::text(style)),
+        ElementKind::List {
+            ordered,
+            start,
+            items,
+        } => {
+            let items = items.iter().enumerate().map(|(i, item)| {
+                let marker = if *ordered {
+                    format!("{}.", start + i as u64)
+                } else {
+                    String::from("•")
+                };
+                list_item(marker, item, style)
+            });
+            Column::with_children(items).spacing(4.0).into()
+        }
+        ElementKind::Item(children) => blocks(children, style),
+        ElementKind::TaskItem { children, .. } => blocks(children, style),
+        ElementKind::BlockQuote(children) => {
+            let quote = Markdown {
+                text: style.quote,
+                ..*style
+            };
+            stack![
+                container(blocks(children, &quote)).padding(Padding::new(0.0).left(16.0)),
+                container(Space::new(3.0, Length::Fill)).style(theming::iced::container::quote_bar),
+            ]
+            .into()
+        }
+        ElementKind::CodeBlock { lang, code } => {
+            let code = code.trim_end_matches('\n');
+            let content = match lang
+                .as_deref()
+                .and_then(|lang| highlight::highlight(code, lang))
+            {
+                Some(tokens) => highlighted_code(code, &tokens, style),
+                None => text(code)
+                    .font(Font::MONOSPACE)
+                    .size(style.text_size)
+                    .into(),
+            };
+
+            container(content)
+                .width(Length::Fill)
+                .padding(8.0)
+                .style(theming::iced::container::code_block)
+                .into()
+        }
+        ElementKind::Table { header, rows, .. } => {
+            let mut table = vec![table_row(header, style, true)];
+            table.extend(rows.iter().map(|row| table_row(row, style, false)));
+            Column::with_children(table).spacing(4.0).into()
+        }
+        ElementKind::Rule => container(Space::new(Length::Fill, 1.0))
+            .style(theming::iced::container::rule)
+            .into(),
+    }
+}
+
+fn list_item<'a>(
+    marker: String,
+    item: &'a Element,
+    style: &Markdown,
+) -> iced::Element<'a, Message, Theme> {
+    let marker = text(match item.kind {
+        ElementKind::TaskItem { checked: true, .. } => String::from("☑"),
+        ElementKind::TaskItem { checked: false, .. } => String::from("☐"),
+        _ => marker,
+    })
+    .size(style.text_size)
+    .color(style.text);
+
+    let marker: iced::Element<'a, Message, Theme> = match item.kind {
+        ElementKind::TaskItem { .. } => text_button(marker)
+            .padding(0.0)
+            .on_press(Message::ToggleTask(item.range.start))
+            .into(),
+        _ => marker.into(),
+    };
+
+    row![marker, element(item, style)].spacing(8.0).into()
+}
+
+fn table_row<'a>(
+    cells: &'a [TableCell],
+    style: &Markdown,
+    header: bool,
 ) -> iced::Element<'a, Message, Theme> {
-    let elements: Vec<iced::Element<'_, Message, Theme>> = buffer
-        .elements
+    let paint = Paint {
+        bold: header,
+        ..Paint::text(style)
+    };
+
+    Row::with_children(cells.iter().map(|cell| {
+        container(inlines(&cell.content, style, paint))
+            .width(Length::Fill)
+            .into()
+    }))
+    .spacing(8.0)
+    .into()
+}
+
+/// Colors code tokens using syntax colors of the active theme
+fn highlighted_code<'a>(
+    code: &'a str,
+    tokens: &Tokens,
+    style: &Markdown,
+) -> iced::Element<'a, Message, Theme> {
+    let syntax = theme!(syntax);
+    let spans: Vec<text::Span<'a, Message, Font>> = tokens
         .iter()
-        .map(|element| match element {
-            core::buffer::Element::Span(label, attrs) => text(label)
-                .size(attrs.font_size)
-                .color(attrs.font_color)
-                .into(),
+        .map(|(range, token)| {
+            span(&code[range.clone()])
+                .font(Font::MONOSPACE)
+                .size(style.text_size)
+                .color(syntax.color(*token).unwrap_or(style.code))
         })
         .collect();
 
-    let column = Column::with_children(elements);
-    column.into()
+    rich_text(spans).into()
+}
+
+/// Base text style of a block, refined by inline attributes
+#[derive(Clone, Copy)]
+struct Paint {
+    size: f32,
+    color: Color,
+    bold: bool,
+}
+
+impl Paint {
+    fn text(style: &Markdown) -> Self {
+        Self {
+            size: style.text_size,
+            color: style.text,
+            bold: false,
+        }
+    }
+}
+
+fn inlines<'a>(
+    content: &'a [Inline],
+    style: &Markdown,
+    paint: Paint,
+) -> iced::Element<'a, Message, Theme> {
+    let mut spans = Vec::new();
+    collect_spans(content, style, paint, false, &mut spans);
+    rich_text(spans).into()
+}
+
+/// Flattens inline tree into text spans
+fn collect_spans<'a>(
+    content: &'a [Inline],
+    style: &Markdown,
+    paint: Paint,
+    link: bool,
+    spans: &mut Vec<text::Span<'a, Message, Font>>,
+) {
+    for inline in content {
+        match &inline.kind {
+            InlineKind::Span(label, attributes) => {
+                let span = span(label.as_str())
+                    .font(font(attributes, paint.bold))
+                    .size(paint.size)
+                    .color(color(attributes, style, paint, link))
+                    .underline(link)
+                    .strikethrough(attributes.strike);
+
+                spans.push(if attributes.monospace {
+                    span.background(style.code_background)
+                } else {
+                    span
+                });
+            }
+            InlineKind::Link { content, .. } => collect_spans(content, style, paint, true, spans),
+            InlineKind::Image { alt, .. } => {
+                let attributes = SpanAttributes {
+                    italic: true,
+                    ..Default::default()
+                };
+                spans.push(
+                    span(alt.as_str())
+                        .font(font(&attributes, paint.bold))
+                        .size(paint.size)
+                        .color(color(&attributes, style, paint, link)),
+                )
+            }
+        }
+    }
+}
+
+/// Picks span color, most specific attribute first
+fn color(attributes: &SpanAttributes, style: &Markdown, paint: Paint, link: bool) -> Color {
+    if link {
+        style.link
+    } else if attributes.monospace {
+        style.code
+    } else if attributes.strike {
+        style.strikethrough
+    } else if attributes.bold {
+        style.strong
+    } else if attributes.italic {
+        style.emphasis
+    } else {
+        paint.color
+    }
+}
+
+fn font(attributes: &SpanAttributes, bold: bool) -> Font {
+    Font {
+        family: if attributes.monospace {
+            Family::Monospace
+        } else {
+            Family::SansSerif
+        },
+        weight: if bold || attributes.bold {
+            Weight::Bold
+        } else {
+            Weight::Normal
+        },
+        style: if attributes.italic {
+            Style::Italic
+        } else {
+            Style::Normal
+        },
+        ..Font::DEFAULT
+    }
 }
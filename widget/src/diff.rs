@@ -0,0 +1,56 @@
+use core::diff::{Change, Diff};
+
+use iced::{
+    widget::{text, Column},
+    Element, Font,
+};
+use theming::{theme, Theme};
+
+/// Unchanged lines shown around each change
+const CONTEXT: usize = 2;
+
+/// Changed lines with a few unchanged ones around them
+pub fn diff<'a, Message: 'a>(diff: &Diff) -> Element<'a, Message, Theme> {
+    let diff = match diff {
+        Diff::Lines(lines) => lines,
+        Diff::TooLarge => return text("File is too large to diff").into(),
+    };
+    let changed: Vec<usize> = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.change != Change::Same)
+        .map(|(i, _)| i)
+        .collect();
+
+    if changed.is_empty() {
+        return text("No changes").into();
+    }
+
+    let mut lines: Vec<Element<'a, Message, Theme>> = Vec::new();
+    let mut last = None;
+    for (i, line) in diff.iter().enumerate() {
+        let near = changed.iter().any(|&change| i.abs_diff(change) <= CONTEXT);
+        if !near {
+            continue;
+        }
+        if last.is_some_and(|last| last + 1 != i) {
+            lines.push(text("…").into());
+        }
+        last = Some(i);
+
+        let (prefix, color) = match line.change {
+            Change::Same => (" ", None),
+            // Syntax colors keep diffs readable in any theme
+            Change::Added => ("+", Some(theme!(syntax.string))),
+            Change::Removed => ("-", Some(theme!(syntax.keyword))),
+        };
+        lines.push(
+            text(format!("{prefix} {}", line.text))
+                .font(Font::MONOSPACE)
+                .color_maybe(color.map(iced::Color::from))
+                .into(),
+        );
+    }
+
+    Column::from_vec(lines).into()
+}
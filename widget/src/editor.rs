@@ -1,14 +1,18 @@
+use std::ops::Range;
+
 use iced::{
     widget::{
         component,
-        text_editor::{self, Content},
+        text_editor::{self, Action, Content, Motion},
         Component, TextEditor,
     },
-    Element, Length,
+    Element, Length, Point,
 };
 
 use theming::{self, Theme};
 
+use crate::highlighter::{self, Highlighter};
+
 /// Text editor widget
 pub struct NoteEditor<'a, Message> {
     content: &'a Content,
@@ -41,6 +45,7 @@ impl<'a, Message> Component<Message, Theme> for NoteEditor<'a, Message> {
             .on_action(&self.on_action)
             .height(Length::Fill)
             .size(16.0)
+            .highlight_with::<Highlighter>((), highlighter::to_format)
             .into()
     }
 }
@@ -53,3 +58,96 @@ where
         component(editor)
     }
 }
+
+/// Returns byte offset of the cursor in the content text
+pub fn cursor_offset(content: &Content) -> usize {
+    let (line, column) = content.cursor_position();
+    line_start(content, line) + column
+}
+
+/// Moves the cursor to byte offset of the content text
+pub fn move_cursor(content: &mut Content, offset: usize) {
+    let position = position(content, offset);
+    content.perform(Action::Move(Motion::DocumentStart));
+    move_to(content, position, Action::Move);
+}
+
+/// Moves the cursor to the start of the line
+pub fn move_to_line(content: &mut Content, line: usize) {
+    let line = line.min(content.line_count().saturating_sub(1));
+    content.perform(Action::Move(Motion::DocumentStart));
+    move_to(content, (line, 0), Action::Move);
+}
+
+/// Returns the first line shown in the editor.
+///
+/// The editor doesn't report its scroll offset, but a click at the top left
+/// corner lands on the first shown line. The cursor is put back afterwards.
+pub fn top_line(content: &mut Content) -> usize {
+    let cursor = cursor_offset(content);
+    content.perform(Action::Click(Point::ORIGIN));
+    let line = content.cursor_position().0;
+    move_cursor(content, cursor);
+    line
+}
+
+/// Scrolls a new editor down by given number of lines. Lines wrapped
+/// in the shown editor scroll it by fewer lines.
+pub fn scroll_to_line(content: &mut Content, line: usize) {
+    content.perform(Action::Scroll {
+        lines: line.try_into().unwrap_or(i32::MAX),
+    });
+}
+
+/// Selects byte range of the content text
+pub fn select(content: &mut Content, range: Range<usize>) {
+    move_cursor(content, range.start);
+
+    let end = position(content, range.end);
+    move_to(content, end, Action::Select);
+}
+
+/// Moves the cursor forward to (line, column), going down by lines first
+fn move_to(content: &mut Content, (line, column): (usize, usize), motion: fn(Motion) -> Action) {
+    // Vertical motions go through wrapped rows, so check the logical line each time
+    if content.cursor_position().0 < line {
+        repeat(content, motion(Motion::Down), |content| {
+            content.cursor_position().0 < line
+        });
+        content.perform(motion(Motion::Home));
+    }
+    repeat(content, motion(Motion::Right), |content| {
+        content.cursor_position() < (line, column)
+    });
+}
+
+/// Performs the action while the condition holds and the cursor moves
+fn repeat(content: &mut Content, action: Action, condition: impl Fn(&Content) -> bool) {
+    while condition(content) {
+        let before = content.cursor_position();
+        content.perform(action.clone());
+        if content.cursor_position() == before {
+            break;
+        }
+    }
+}
+
+/// Returns (line, column) of byte offset of the content text
+fn position(content: &Content, offset: usize) -> (usize, usize) {
+    let mut start = 0;
+    for line in 0..content.line_count() {
+        let len = content.line(line).map_or(0, |text| text.len());
+        if offset <= start + len {
+            return (line, offset - start);
+        }
+        start += len + 1;
+    }
+    let last = content.line_count().saturating_sub(1);
+    (last, content.line(last).map_or(0, |text| text.len()))
+}
+
+fn line_start(content: &Content, line: usize) -> usize {
+    (0..line)
+        .map(|line| content.line(line).map_or(0, |text| text.len()) + 1)
+        .sum()
+}
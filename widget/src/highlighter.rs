@@ -0,0 +1,151 @@
+use std::ops::Range;
+
+use core::{
+    highlight::{self, Token},
+    markdown::{self, Fence, Markup},
+};
+
+use iced::advanced::text::{self, highlighter::Format};
+use iced::{
+    font::{Style, Weight},
+    Font,
+};
+use theming::Theme;
+
+/// Highlighted fragment of a note
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    Markup(Markup),
+    /// Token inside a fenced code block
+    Code(Token),
+}
+
+/// Highlights markdown syntax of a note and code inside its fenced blocks
+pub struct Highlighter {
+    /// Block state at the start of each highlighted line
+    states: Vec<Block>,
+    current_line: usize,
+}
+
+#[derive(Clone)]
+enum Block {
+    Text,
+    Code {
+        fence: Fence,
+        highlighter: Option<highlight::Highlighter>,
+    },
+}
+
+impl Block {
+    /// Advances the state past the line, returning its highlights
+    fn next(&mut self, line: &str) -> Vec<(Range<usize>, Highlight)> {
+        match self {
+            Block::Text => {
+                if let Some((fence, info)) = Fence::open(line) {
+                    *self = Block::Code {
+                        fence,
+                        highlighter: info
+                            .split_whitespace()
+                            .next()
+                            .and_then(highlight::Highlighter::new),
+                    };
+                    return vec![(0..line.len(), Highlight::Markup(Markup::Marker))];
+                }
+
+                markdown::highlight_line(line)
+                    .into_iter()
+                    .map(|(range, markup)| (range, Highlight::Markup(markup)))
+                    .collect()
+            }
+            Block::Code { fence, .. } if fence.closes(line) => {
+                *self = Block::Text;
+                vec![(0..line.len(), Highlight::Markup(Markup::Marker))]
+            }
+            Block::Code {
+                highlighter: Some(highlighter),
+                ..
+            } => highlighter
+                .highlight_line(line)
+                .into_iter()
+                .map(|(range, token)| (range, Highlight::Code(token)))
+                .collect(),
+            Block::Code {
+                highlighter: None, ..
+            } => vec![(0..line.len(), Highlight::Code(Token::Plain))],
+        }
+    }
+}
+
+impl text::Highlighter for Highlighter {
+    type Settings = ();
+    type Highlight = Highlight;
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Highlight)>;
+
+    fn new(_settings: &Self::Settings) -> Self {
+        Self {
+            states: vec![Block::Text],
+            current_line: 0,
+        }
+    }
+
+    fn update(&mut self, _new_settings: &Self::Settings) {}
+
+    fn change_line(&mut self, line: usize) {
+        self.current_line = line.min(self.states.len() - 1);
+        self.states.truncate(self.current_line + 1);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        let mut state = self.states[self.current_line].clone();
+        let highlights = state.next(line);
+
+        self.states.push(state);
+        self.current_line += 1;
+
+        highlights.into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.current_line
+    }
+}
+
+/// Resolves highlight color and font from the theme
+pub fn to_format(highlight: &Highlight, theme: &Theme) -> Format<Font> {
+    let editor = &theme.editor;
+    let (color, font) = match highlight {
+        Highlight::Markup(markup) => match markup {
+            Markup::Heading => (editor.heading, Some(bold())),
+            Markup::Marker => (editor.marker, None),
+            Markup::Strong => (editor.strong, Some(bold())),
+            Markup::Emphasis => (
+                editor.emphasis,
+                Some(Font {
+                    style: Style::Italic,
+                    ..Font::DEFAULT
+                }),
+            ),
+            Markup::Strikethrough => (editor.strikethrough, None),
+            Markup::Code => (editor.code, Some(Font::MONOSPACE)),
+            Markup::Link => (editor.link, None),
+            Markup::ListMarker => (editor.list_marker, None),
+            Markup::Quote => (editor.quote, None),
+        },
+        Highlight::Code(token) => (
+            theme.syntax.color(*token).unwrap_or(editor.code),
+            Some(Font::MONOSPACE),
+        ),
+    };
+
+    Format {
+        color: Some(color.into()),
+        font,
+    }
+}
+
+fn bold() -> Font {
+    Font {
+        weight: Weight::Bold,
+        ..Font::DEFAULT
+    }
+}
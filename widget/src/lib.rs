@@ -1,7 +1,9 @@
 pub mod buffer;
 pub mod button;
 pub mod container;
+pub mod diff;
 pub mod editor;
+pub mod highlighter;
 pub mod list;
 pub mod pane;
 pub mod tab;
@@ -0,0 +1,73 @@
+use core::{document::DocumentId, vault::note_name};
+use std::path::{Path, PathBuf};
+
+use iced::{
+    widget::{center, column, scrollable, text, Column},
+    Element, Font, Length, Padding,
+};
+use state::State;
+use theming::{theme, Theme};
+
+use crate::{
+    button::text_button,
+    container::{background, background2},
+};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    OpenNote(PathBuf),
+}
+
+/// Lists notes linking to the document with lines they link from
+pub fn backlinks_pane(id: DocumentId, state: &State) -> Element<'_, Message, Theme> {
+    let Some(handler) = state.documents.get(&id) else {
+        return background(center(text("Document is closed"))).into();
+    };
+
+    let title = handler
+        .title()
+        .map(String::from)
+        .unwrap_or_else(|| note_name(Path::new(&handler.filename)));
+
+    // Nothing can link to an untitled document
+    let backlinks = match &handler.path {
+        Some(path) => state.links.backlinks(path),
+        None => Vec::new(),
+    };
+    let entries: Vec<Element<'_, Message, Theme>> = backlinks
+        .iter()
+        .map(|backlink| {
+            background2(
+                column![
+                    text_button(text(note_name(backlink.source)).font(Font {
+                        weight: iced::font::Weight::Bold,
+                        ..Default::default()
+                    }))
+                    .on_press(Message::OpenNote(backlink.source.to_path_buf())),
+                    text(backlink.link.context.clone()),
+                ]
+                .spacing(4.0),
+            )
+            .width(Length::Fill)
+            .padding(Padding::new(8.0).left(16.0).right(16.0))
+            .into()
+        })
+        .collect();
+
+    let header = if entries.is_empty() {
+        format!("No notes link to {title}")
+    } else {
+        format!("{} backlinks to {title}", entries.len())
+    };
+
+    background(center(
+        column![
+            text(header).size(20.0),
+            scrollable(Column::from_vec(entries).spacing(8.0)),
+        ]
+        .spacing(16.0)
+        .width(theme!(editor.width))
+        .padding(Padding::new(16.0)),
+    ))
+    .into()
+}
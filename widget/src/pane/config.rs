@@ -54,6 +54,9 @@ pub fn config_value<'a>(value: &Value) -> Element<'a, (), Theme> {
         .into(),
         Value::Path(_) => todo!(),
         Value::String(string) => text(format!("\"{}\"", string)).into(),
+        Value::List(values) => Column::with_children(values.iter().map(config_value))
+            .spacing(2.0)
+            .into(),
     };
 
     let value_type = match value {
@@ -63,6 +66,7 @@ pub fn config_value<'a>(value: &Value) -> Element<'a, (), Theme> {
         Value::Color(_) => "Color",
         Value::Path(_) => "Path",
         Value::String(_) => "String",
+        Value::List(_) => "List",
     };
 
     row![text(value_type), text(":"), value_view]
@@ -1,4 +1,8 @@
+pub mod backlinks;
 pub mod config;
 pub mod new_document;
 pub mod pane_stack;
+pub mod recovery;
+pub mod tags;
+pub mod tasks;
 pub mod text_editor;
@@ -9,6 +9,7 @@ use crate::{button::text_button, container::background};
 #[derive(Debug, Clone, Copy)]
 pub enum Message {
     PickFile,
+    NewFile,
 }
 
 pub fn new_document_pane<'a>() -> Element<'a, Message, Theme> {
@@ -26,6 +27,8 @@ pub fn new_document_pane<'a>() -> Element<'a, Message, Theme> {
             .height(Length::Shrink),
             center(text_button("Open file Ctrl+O").on_press(Message::PickFile))
                 .height(Length::Shrink),
+            center(text_button("New file Ctrl+N").on_press(Message::NewFile))
+                .height(Length::Shrink),
         ]
         .spacing(16.0),
     ))
@@ -1,24 +1,33 @@
 use core::{
     buffer::{Buffer, FormattedBuffer},
-    document::DocumentId,
-    pane::{Pane, PaneId},
+    document::{DocumentHandler, DocumentId},
+    pane::{Axis, GroupId, Layout, Pane, PaneId},
 };
 use state::State;
+use std::path::PathBuf;
 
 use iced::{
-    widget::{column, svg, Space},
-    Element, Length,
+    widget::{
+        column, container, row, scrollable, svg,
+        text_editor::{Action, Content},
+        Space,
+    },
+    Element, Length, Padding,
 };
 use theming::Theme;
 
 use crate::{
-    buffer::buffer,
+    buffer::{self, buffer},
     container::background,
     pane::{
+        backlinks::{self, backlinks_pane},
         new_document::{self, new_document_pane},
+        recovery::{self, recovery_pane},
+        tags::{self, tags_pane},
+        tasks::{self, tasks_pane},
         text_editor,
     },
-    tab::{tab_bar, Tab},
+    tab::{reorderable_tab_bar, Tab},
     util::filename,
     Label,
 };
@@ -26,7 +35,7 @@ use crate::{
 use super::config::config_pane;
 
 lazy_static::lazy_static! {
-    pub static ref BUFFER: Buffer = Buffer::new("Hello\nAboba");
+    pub static ref BUFFER: Buffer = Buffer::new(include_str!("../../../content/sample.md"));
     pub static ref FORMATTED: FormattedBuffer = FormattedBuffer::from_buffer(&BUFFER);
 }
 
@@ -34,38 +43,115 @@ lazy_static::lazy_static! {
 pub enum Message {
     OpenPane(PaneId),
     ClosePane(PaneId),
+    /// Tab of the pane was dragged to the index
+    MovePane(PaneId, usize),
     NewPane(Pane),
     NewDocument(new_document::Message),
     TextEditor(DocumentId, text_editor::Message),
+    Backlinks(backlinks::Message),
+    Preview(DocumentId, buffer::Message),
+    Tags(tags::Message),
+    Tasks(tasks::Message),
+    Recovery(recovery::Message),
+    /// Message from a tab group that isn't focused
+    Focus(GroupId, Box<Message>),
     None,
 }
 
+/// Tab groups of the split layout
 pub fn pane_stack(state: &State) -> Element<'_, Message, Theme> {
-    let open = state.panes.get_open_id().unwrap_or(&0);
+    layout(state.panes.layout(), state)
+}
+
+fn layout<'a>(node: &'a Layout, state: &'a State) -> Element<'a, Message, Theme> {
+    match node {
+        Layout::Group(id) => {
+            let id = *id;
+            let group = tab_group(id, state);
+            if id == state.panes.focused() {
+                group
+            } else {
+                group.map(move |message| match message {
+                    // Scrolling a group doesn't focus it
+                    Message::TextEditor(
+                        _,
+                        text_editor::Message::EditorAction(Action::Scroll { .. }),
+                    ) => message,
+                    message => Message::Focus(id, Box::new(message)),
+                })
+            }
+        }
+        Layout::Split {
+            axis,
+            ratio,
+            first,
+            second,
+        } => {
+            let portion = (ratio * 1000.0) as u16;
+            let (first_size, second_size) = (
+                Length::FillPortion(portion),
+                Length::FillPortion(1000 - portion),
+            );
+            let first = container(layout(first, state));
+            let second = container(layout(second, state));
+            match axis {
+                Axis::Row => row![first.width(first_size), second.width(second_size)]
+                    .spacing(2.0)
+                    .into(),
+                Axis::Column => column![first.height(first_size), second.height(second_size)]
+                    .spacing(2.0)
+                    .into(),
+            }
+        }
+    }
+}
 
-    let mut tabs: Vec<Tab<Message>> = state
-        .panes
-        .list()
+/// Tab bar and the open pane of the group
+fn tab_group(id: GroupId, state: &State) -> Element<'_, Message, Theme> {
+    let Some(group) = state.panes.group(&id) else {
+        return Space::new(Length::Fill, Length::Fill).into();
+    };
+    let open = group.open();
+    let ids: Vec<PaneId> = group
+        .tabs()
         .iter()
+        .filter(|id| state.panes.contains(id))
+        .copied()
+        .collect();
+
+    let mut tabs: Vec<Tab<Message>> = ids
+        .iter()
+        .filter_map(|id| state.panes.get(id).map(|pane| (id, pane)))
         .map(|(id, pane)| {
-            let title: Option<Label> = match **pane {
+            let title: Option<Label> = match *pane {
                 Pane::Empty => None,
                 Pane::NewDocument => Some("New tab".into()),
                 Pane::Editor(id) => state
                     .documents
                     .get(&id)
-                    .map(|handler| filename(handler.path.clone()).unwrap_or_default().into()),
+                    .map(|handler| document_title(handler).into()),
                 Pane::Buffer => Some("Buffer tab (EXPERIMENTAL)".into()),
                 Pane::Config => Some("Config viewer".into()),
+                Pane::Backlinks(id) => state
+                    .documents
+                    .get(&id)
+                    .map(|handler| format!("Backlinks: {}", document_title(handler)).into()),
+                Pane::Preview(id) => state
+                    .documents
+                    .get(&id)
+                    .map(|handler| format!("Preview: {}", document_title(handler)).into()),
+                Pane::Tags => Some("Tags".into()),
+                Pane::Tasks => Some("Tasks".into()),
+                Pane::Recovery => Some("Recovery".into()),
             };
 
             Tab {
                 label: title,
                 icon: None,
-                selected: *id == open,
-                on_click: Some(Message::OpenPane(**id)),
-                on_close: Some(Message::ClosePane(**id)),
-                on_middle_click: Some(Message::ClosePane(**id)),
+                selected: Some(*id) == open,
+                on_click: Some(Message::OpenPane(*id)),
+                on_close: Some(Message::ClosePane(*id)),
+                on_middle_click: Some(Message::ClosePane(*id)),
             }
         })
         .collect();
@@ -81,16 +167,33 @@ pub fn pane_stack(state: &State) -> Element<'_, Message, Theme> {
 
     tabs.push(new_tab_button);
 
-    let tab_bar = tab_bar(tabs);
+    let count = ids.len();
+    let tab_bar = reorderable_tab_bar(tabs, count, move |from, to| {
+        Message::MovePane(ids[from], to)
+    });
 
-    let pane = if let Some(pane) = state.panes.get_open() {
+    let pane = if let Some(pane) = open.and_then(|id| state.panes.get(&id)) {
         match *pane {
             Pane::Empty => background(Space::new(Length::Fill, Length::Fill)).into(),
             Pane::NewDocument => new_document_pane().map(Message::NewDocument),
             Pane::Editor(id) => text_editor::text_editor(id, state)
                 .map(move |action| Message::TextEditor(id, action)),
-            Pane::Buffer => background(buffer(&FORMATTED)).into(),
+            // Sample isn't a document, so its tasks can't be toggled
+            Pane::Buffer => {
+                background(scrollable(buffer(&FORMATTED).map(|_| Message::None))).into()
+            }
             Pane::Config => config_pane(state).map(|_| Message::None),
+            Pane::Backlinks(id) => backlinks_pane(id, state).map(Message::Backlinks),
+            Pane::Preview(id) => match state.previews.get(&id) {
+                Some((_, _, preview)) => {
+                    let preview = buffer(preview).map(move |message| Message::Preview(id, message));
+                    background(scrollable(container(preview).padding(Padding::new(16.0)))).into()
+                }
+                None => background(Space::new(Length::Fill, Length::Fill)).into(),
+            },
+            Pane::Tags => tags_pane(state).map(Message::Tags),
+            Pane::Tasks => tasks_pane(state).map(Message::Tasks),
+            Pane::Recovery => recovery_pane(state).map(Message::Recovery),
         }
     } else {
         Space::new(Length::Fill, Length::Fill).into()
@@ -98,3 +201,11 @@ pub fn pane_stack(state: &State) -> Element<'_, Message, Theme> {
 
     column![tab_bar, pane,].into()
 }
+
+/// Title of the document from its metadata, or its file name
+fn document_title(handler: &DocumentHandler<Content>) -> String {
+    match handler.title() {
+        Some(title) => title.to_string(),
+        None => filename(PathBuf::from(&handler.filename)).unwrap_or_default(),
+    }
+}
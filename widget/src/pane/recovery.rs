@@ -0,0 +1,81 @@
+use core::recovery::Key;
+
+use iced::{
+    widget::{center, column, row, scrollable, text, Column, Space},
+    Element, Font, Length, Padding,
+};
+use state::State;
+use theming::{theme, Theme};
+
+use crate::{
+    button::{primary_button, secondary_button},
+    container::{background, background2},
+    diff::diff,
+};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Opens the journaled text in an editor
+    Restore(Key),
+    /// Deletes the journaled text
+    Discard(Key),
+}
+
+/// Lists documents left unsaved by a previous run with changes against the saved files
+pub fn recovery_pane(state: &State) -> Element<'_, Message, Theme> {
+    let entries: Vec<Element<'_, Message, Theme>> = state
+        .recovery
+        .iter()
+        .map(|entry| {
+            let title = match &entry.key {
+                Key::File(path) => path.to_string_lossy().to_string(),
+                Key::Untitled(_) => String::from("Untitled document"),
+            };
+            let title = text(title).font(Font {
+                weight: iced::font::Weight::Bold,
+                ..Default::default()
+            });
+
+            let changes: Element<'_, Message, Theme> = match &entry.diff {
+                Some(changes) => diff(changes),
+                None => text("File no longer exists on disk").into(),
+            };
+
+            background2(
+                column![
+                    row![
+                        title,
+                        Space::with_width(Length::Fill),
+                        primary_button(text("Restore"))
+                            .on_press(Message::Restore(entry.key.clone())),
+                        secondary_button(text("Discard"))
+                            .on_press(Message::Discard(entry.key.clone())),
+                    ]
+                    .spacing(8.0),
+                    changes,
+                ]
+                .spacing(8.0),
+            )
+            .width(Length::Fill)
+            .padding(Padding::new(8.0).left(16.0).right(16.0))
+            .into()
+        })
+        .collect();
+
+    let header = if entries.is_empty() {
+        String::from("Nothing to recover")
+    } else {
+        format!("{} documents have unsaved changes", entries.len())
+    };
+
+    background(center(
+        column![
+            text(header).size(20.0),
+            scrollable(Column::from_vec(entries).spacing(8.0)),
+        ]
+        .spacing(16.0)
+        .width(theme!(editor.width))
+        .padding(Padding::new(16.0)),
+    ))
+    .into()
+}
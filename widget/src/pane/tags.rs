@@ -0,0 +1,86 @@
+use core::vault::note_name;
+use std::path::PathBuf;
+
+use iced::{
+    widget::{column, container, row, scrollable, text, Column, Space},
+    Element, Length, Padding,
+};
+use state::State;
+use theming::Theme;
+
+use crate::{
+    button::{primary_button, text_button},
+    container::{background, background2},
+};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SelectTag(String),
+    OpenNote(PathBuf),
+}
+
+/// Tag tree of the workdir with notes of the selected tag
+pub fn tags_pane(state: &State) -> Element<'_, Message, Theme> {
+    let selected = state.selected_tag.as_deref();
+
+    let tags: Vec<Element<'_, Message, Theme>> = state
+        .tags
+        .tree()
+        .into_iter()
+        .map(|node| {
+            let label = row![
+                Space::with_width(Length::Fixed(16.0 * node.depth as f32)),
+                text(format!("#{}", node.label)),
+                Space::with_width(Length::Fill),
+                text(node.count.to_string()),
+            ];
+            let button = if Some(node.tag.as_str()) == selected {
+                primary_button(label)
+            } else {
+                text_button(label)
+            };
+            button
+                .width(Length::Fill)
+                .on_press(Message::SelectTag(node.tag))
+                .into()
+        })
+        .collect();
+
+    let tags: Element<'_, Message, Theme> = if tags.is_empty() {
+        text("No tags found in the workdir").into()
+    } else {
+        scrollable(Column::from_vec(tags).spacing(2.0)).into()
+    };
+
+    let notes: Element<'_, Message, Theme> = match selected {
+        Some(tag) => {
+            let notes = state.tags.notes(tag).into_iter().map(|path| {
+                text_button(text(note_name(path)))
+                    .width(Length::Fill)
+                    .on_press(Message::OpenNote(path.to_path_buf()))
+                    .into()
+            });
+            column![
+                text(format!("Notes tagged #{tag}")).size(20.0),
+                scrollable(Column::with_children(notes).spacing(2.0)),
+            ]
+            .spacing(16.0)
+            .into()
+        }
+        None => text("Select a tag to see its notes").into(),
+    };
+
+    background(
+        row![
+            background2(tags)
+                .width(Length::FillPortion(1))
+                .height(Length::Fill)
+                .padding(Padding::new(8.0)),
+            container(notes)
+                .width(Length::FillPortion(2))
+                .padding(Padding::new(16.0)),
+        ]
+        .spacing(8.0),
+    )
+    .into()
+}
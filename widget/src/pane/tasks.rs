@@ -0,0 +1,80 @@
+use core::vault::note_name;
+use std::path::PathBuf;
+
+use iced::{
+    widget::{center, column, row, scrollable, text, Column, Space},
+    Element, Font, Length, Padding,
+};
+use state::State;
+use theming::{theme, Theme};
+
+use crate::{
+    button::text_button,
+    container::{background, background2},
+};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Checks the task on the line of the note
+    Toggle(PathBuf, usize),
+    /// Opens the note at the task line
+    Open(PathBuf, usize),
+}
+
+/// Lists open tasks of the workdir grouped by note
+pub fn tasks_pane(state: &State) -> Element<'_, Message, Theme> {
+    let groups = state.tasks.open();
+    let count: usize = groups.iter().map(|(_, tasks)| tasks.len()).sum();
+
+    let entries: Vec<Element<'_, Message, Theme>> = groups
+        .into_iter()
+        .map(|(path, tasks)| {
+            let tasks = tasks.into_iter().map(|task| {
+                let due: Element<'_, Message, Theme> = match &task.due {
+                    Some(due) => text(format!("📅 {due}")).into(),
+                    None => Space::with_width(Length::Shrink).into(),
+                };
+                row![
+                    text_button(text("☐")).on_press(Message::Toggle(path.to_path_buf(), task.line)),
+                    text_button(text(task.text.clone()))
+                        .width(Length::Fill)
+                        .on_press(Message::Open(path.to_path_buf(), task.line)),
+                    due,
+                ]
+                .spacing(8.0)
+                .into()
+            });
+
+            background2(
+                column![
+                    text(note_name(path)).font(Font {
+                        weight: iced::font::Weight::Bold,
+                        ..Default::default()
+                    }),
+                    Column::with_children(tasks).spacing(2.0),
+                ]
+                .spacing(4.0),
+            )
+            .width(Length::Fill)
+            .padding(Padding::new(8.0).left(16.0).right(16.0))
+            .into()
+        })
+        .collect();
+
+    let header = if count == 0 {
+        String::from("No open tasks in the workdir")
+    } else {
+        format!("{count} open tasks")
+    };
+
+    background(center(
+        column![
+            text(header).size(20.0),
+            scrollable(Column::from_vec(entries).spacing(8.0)),
+        ]
+        .spacing(16.0)
+        .width(theme!(editor.width))
+        .padding(Padding::new(16.0)),
+    ))
+    .into()
+}
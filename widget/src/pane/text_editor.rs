@@ -1,5 +1,10 @@
-use core::{document::DocumentId, value::Value};
-use state::State;
+use core::{
+    document::{DocumentHandler, DocumentId},
+    encoding::TextFormat,
+    markdown::OutlineEntry,
+    value::Value,
+};
+use state::{Conflict, State};
 use std::{
     path::{Path, PathBuf},
     str::FromStr,
@@ -7,16 +12,62 @@ use std::{
 
 use iced::{
     border::Radius,
-    widget::{center, column, container, text, text_editor::Action, Row, Space},
+    widget::{
+        center, column, container, row, scrollable, text,
+        text_editor::{Action, Content},
+        text_input, Button, Column, Row, Space,
+    },
     Alignment, Border, Element, Length,
 };
 use theming::{theme, Theme};
 
-use crate::{editor::NoteEditor, util::filename};
+use crate::{
+    button::{primary_button, secondary_button, text_button},
+    container::background2,
+    diff::diff,
+    editor::NoteEditor,
+    util::filename,
+};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     EditorAction(Action),
+    Find(FindMessage),
+    /// Moves the cursor to the heading at the line
+    JumpToLine(usize),
+    /// Changes encoding and line endings the document is saved with
+    SetFormat(TextFormat),
+    Conflict(ConflictMessage),
+}
+
+/// Answer to a change of the file on disk
+#[derive(Debug, Clone)]
+pub enum ConflictMessage {
+    /// Keep the document text, overwriting the file on next save
+    KeepMine,
+    /// Replace the document text with the file, or close it if the file was deleted
+    TakeTheirs,
+    ToggleDiff,
+}
+
+#[derive(Debug, Clone)]
+pub enum FindMessage {
+    Open,
+    Close,
+    Query(String),
+    Replacement(String),
+    ToggleCaseSensitive,
+    ToggleWholeWord,
+    ToggleRegex,
+    Next,
+    Previous,
+    Replace,
+    ReplaceAll,
+}
+
+/// Id of the find bar query input
+pub fn find_input_id() -> text_input::Id {
+    text_input::Id::new("find-query")
 }
 
 fn get_directories_between<'a>(base: &'a Path, target: &'a Path) -> Vec<String> {
@@ -42,16 +93,19 @@ pub fn text_editor(id: DocumentId, state: &State) -> Element<'_, Message, Theme>
                 PathBuf::new()
             };
 
-        let mut folders = get_directories_between(
-            {
-                if handler.path.starts_with(&working_directory) {
-                    working_directory.as_path()
-                } else {
-                    Path::new("/")
-                }
-            },
-            &handler.path,
-        );
+        let mut folders = match &handler.path {
+            Some(path) => get_directories_between(
+                {
+                    if path.starts_with(&working_directory) {
+                        working_directory.as_path()
+                    } else {
+                        Path::new("/")
+                    }
+                },
+                path,
+            ),
+            None => vec![handler.filename.clone()],
+        };
         if let Some(last) = folders.last_mut() {
             *last = filename(PathBuf::from(last.clone())).unwrap_or_default();
         }
@@ -64,7 +118,46 @@ pub fn text_editor(id: DocumentId, state: &State) -> Element<'_, Message, Theme>
                 items.push(text("/").into());
             }
         }
-        Row::with_children(items).spacing(4.0).into()
+
+        let save_error = state.save_errors.get(&id).map(|error| {
+            container(text(error))
+                .padding([2.0, 8.0])
+                .style(|theme: &Theme| container::Style {
+                    text_color: Some(theme.notification.text.into()),
+                    background: Some(theme.notification.background.into()),
+                    border: Border::default()
+                        .rounded(theme.notification.radius)
+                        .color(theme.notification.border_color)
+                        .width(theme.notification.border_width),
+                    ..Default::default()
+                })
+        });
+
+        // Buttons switch to the next encoding or the other line ending
+        let format = handler.format;
+        row![
+            Space::with_width(Length::Fill),
+            Row::with_children(items).spacing(4.0),
+            Space::with_width(Length::Fill),
+        ]
+        .push_maybe(save_error)
+        .extend([
+            text_button(text(format.encoding.name()))
+                .on_press(Message::SetFormat(TextFormat {
+                    encoding: format.encoding.next(),
+                    ..format
+                }))
+                .into(),
+            text_button(text(format.line_ending.name()))
+                .on_press(Message::SetFormat(TextFormat {
+                    line_ending: format.line_ending.toggle(),
+                    ..format
+                }))
+                .into(),
+        ])
+        .spacing(4.0)
+        .align_y(Alignment::Center)
+        .into()
     } else {
         text("").into()
     };
@@ -72,31 +165,187 @@ pub fn text_editor(id: DocumentId, state: &State) -> Element<'_, Message, Theme>
     if let Some(handler) = state.documents.get(&id) {
         let width = theme!(editor.width);
 
-        let editor = container(center(column![
-            container(title)
-                .height(theme!(editor.padding))
-                .width(width)
-                .align_x(Alignment::Center)
-                .align_y(Alignment::Center),
-            container(NoteEditor::new(
-                &handler.text_content,
-                Message::EditorAction,
-            ))
-            .width(width)
-            .padding(theme!(editor.padding))
-            .style(|theme: &Theme| container::Style {
-                text_color: Some(theme.editor.text.into()),
-                background: Some(theme.editor.background2.into()),
-                border: Border::default().rounded(Radius {
-                    top_left: theme.editor.radius,
-                    top_right: theme.editor.radius,
-                    ..Default::default()
-                }),
-                ..Default::default()
-            }),
-        ]));
-        editor.into()
+        let find_bar = state
+            .find
+            .visible
+            .then(|| container(find_bar(state)).width(width));
+
+        let conflict_bar = state
+            .conflicts
+            .get(&id)
+            .map(|conflict| container(conflict_bar(conflict)).width(width));
+
+        let editor = container(center(
+            Column::new()
+                .push(
+                    container(title)
+                        .height(theme!(editor.padding))
+                        .width(width)
+                        .align_x(Alignment::Center)
+                        .align_y(Alignment::Center),
+                )
+                .push_maybe(conflict_bar)
+                .push_maybe(find_bar)
+                .push(
+                    container(NoteEditor::new(
+                        &handler.text_content,
+                        Message::EditorAction,
+                    ))
+                    .width(width)
+                    .padding(theme!(editor.padding))
+                    .style(|theme: &Theme| container::Style {
+                        text_color: Some(theme.editor.text.into()),
+                        background: Some(theme.editor.background2.into()),
+                        border: Border::default().rounded(Radius {
+                            top_left: theme.editor.radius,
+                            top_right: theme.editor.radius,
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                ),
+        ));
+
+        if state.show_outline {
+            let entries = state
+                .outlines
+                .get(&id)
+                .map_or(&[][..], |(_, entries)| entries);
+            row![outline(handler, entries), editor].into()
+        } else {
+            editor.into()
+        }
     } else {
         container(Space::new(Length::Fill, Length::Fill)).into()
     }
 }
+
+/// Sidebar with document headings, the one containing the cursor is highlighted
+fn outline<'a>(
+    handler: &DocumentHandler<Content>,
+    outline: &'a [OutlineEntry],
+) -> Element<'a, Message, Theme> {
+    let cursor = handler.text_content.cursor_position().0;
+    let current = outline.iter().rposition(|entry| entry.line <= cursor);
+
+    let entries = outline.iter().enumerate().map(|(i, entry)| {
+        let label = row![
+            Space::with_width(Length::Fixed(12.0 * (entry.level - 1) as f32)),
+            text(entry.title.as_str()),
+        ];
+        let button = if Some(i) == current {
+            primary_button(label)
+        } else {
+            text_button(label)
+        };
+        button
+            .width(Length::Fill)
+            .on_press(Message::JumpToLine(entry.line))
+            .into()
+    });
+
+    let content: Element<'a, Message, Theme> = if outline.is_empty() {
+        text("No headings").into()
+    } else {
+        scrollable(Column::with_children(entries).spacing(2.0)).into()
+    };
+
+    background2(content)
+        .width(Length::Fixed(240.0))
+        .height(Length::Fill)
+        .padding(8.0)
+        .into()
+}
+
+fn find_bar(state: &State) -> Element<'_, Message, Theme> {
+    let find = &state.find;
+
+    let status = match find.matches {
+        Some(count) => match find.current {
+            Some(current) if current < count => format!("{} of {count}", current + 1),
+            _ => format!("{count} matches"),
+        },
+        None => String::from("Invalid pattern"),
+    };
+
+    let query = row![
+        text_input("Find", &find.query.pattern)
+            .id(find_input_id())
+            .on_input(|pattern| Message::Find(FindMessage::Query(pattern)))
+            .on_submit(Message::Find(FindMessage::Next)),
+        toggle(
+            "Aa",
+            find.query.case_sensitive,
+            FindMessage::ToggleCaseSensitive
+        ),
+        toggle("W", find.query.whole_word, FindMessage::ToggleWholeWord),
+        toggle(".*", find.query.regex, FindMessage::ToggleRegex),
+        text(status),
+        text_button(text("Prev")).on_press(Message::Find(FindMessage::Previous)),
+        text_button(text("Next")).on_press(Message::Find(FindMessage::Next)),
+        text_button(text("Close")).on_press(Message::Find(FindMessage::Close)),
+    ]
+    .spacing(4.0)
+    .align_y(Alignment::Center);
+
+    let replace = row![
+        text_input("Replace", &find.replacement)
+            .on_input(|replacement| Message::Find(FindMessage::Replacement(replacement)))
+            .on_submit(Message::Find(FindMessage::Replace)),
+        text_button(text("Replace")).on_press(Message::Find(FindMessage::Replace)),
+        text_button(text("Replace all")).on_press(Message::Find(FindMessage::ReplaceAll)),
+    ]
+    .spacing(4.0)
+    .align_y(Alignment::Center);
+
+    column![query, replace].spacing(4.0).padding(8.0).into()
+}
+
+fn conflict_bar(conflict: &Conflict) -> Element<'_, Message, Theme> {
+    let (status, theirs) = match conflict {
+        Conflict::Changed { .. } => ("File was changed on disk", "Take theirs"),
+        Conflict::Deleted => ("File was deleted from disk", "Close"),
+    };
+
+    let mut buttons = row![
+        text(status),
+        Space::with_width(Length::Fill),
+        primary_button(text("Keep mine")).on_press(Message::Conflict(ConflictMessage::KeepMine)),
+        secondary_button(text(theirs)).on_press(Message::Conflict(ConflictMessage::TakeTheirs)),
+    ]
+    .spacing(4.0)
+    .align_y(Alignment::Center);
+
+    let diff = match conflict {
+        Conflict::Changed { diff: changes, .. } => {
+            buttons = buttons.push(
+                text_button(text(if changes.is_some() {
+                    "Hide diff"
+                } else {
+                    "View diff"
+                }))
+                .on_press(Message::Conflict(ConflictMessage::ToggleDiff)),
+            );
+            changes
+                .as_ref()
+                .map(|changes| scrollable(diff(changes)).height(Length::Fixed(240.0)))
+        }
+        Conflict::Deleted => None,
+    };
+
+    column![buttons]
+        .push_maybe(diff)
+        .spacing(4.0)
+        .padding(8.0)
+        .into()
+}
+
+/// Search option button, highlighted when enabled
+fn toggle<'a>(label: &'a str, enabled: bool, message: FindMessage) -> Button<'a, Message, Theme> {
+    let button = if enabled {
+        primary_button(text(label))
+    } else {
+        secondary_button(text(label))
+    };
+    button.on_press(Message::Find(message))
+}
@@ -1,6 +1,12 @@
 use iced::{
+    advanced::{
+        layout, mouse, renderer,
+        widget::{tree, Operation, Tree},
+        Clipboard, Layout, Shell, Widget,
+    },
+    event,
     widget::{container, svg, text, MouseArea, Row, Space},
-    Alignment, Element, Length, Padding,
+    Alignment, Element, Event, Length, Padding, Point, Rectangle, Renderer, Size,
 };
 use theming::{iced::container::background, theme, Theme};
 
@@ -78,8 +84,30 @@ pub fn tab<'a, Message: Clone + 'a>(tab: &Tab<Message>) -> Element<'a, Message,
 pub fn tab_bar<'a, Message: Clone + 'a>(tabs: Vec<Tab<Message>>) -> Element<'a, Message, Theme> {
     let tabs = tabs.iter().map(tab);
 
-    let content = container(Row::with_children(tabs).spacing(theme!(tab_bar.spacing)))
-        .padding(theme!(tab_bar.padding));
+    bar(Row::with_children(tabs)
+        .spacing(theme!(tab_bar.spacing))
+        .into())
+}
+
+/// Tab bar whose first `count` tabs can be reordered by dragging.
+/// `on_reorder` gets the index of the dragged tab and the index it was dropped at.
+pub fn reorderable_tab_bar<'a, Message: Clone + 'a>(
+    tabs: Vec<Tab<Message>>,
+    count: usize,
+    on_reorder: impl Fn(usize, usize) -> Message + 'a,
+) -> Element<'a, Message, Theme> {
+    let row = Row::with_children(tabs.iter().map(tab)).spacing(theme!(tab_bar.spacing));
+
+    bar(Reorder {
+        content: row.into(),
+        count,
+        on_reorder: Box::new(on_reorder),
+    }
+    .into())
+}
+
+fn bar<'a, Message: 'a>(tabs: Element<'a, Message, Theme>) -> Element<'a, Message, Theme> {
+    let content = container(tabs).padding(theme!(tab_bar.padding));
 
     container(content)
         .width(Length::Fill)
@@ -90,3 +118,209 @@ pub fn tab_bar<'a, Message: Clone + 'a>(tabs: Vec<Tab<Message>>) -> Element<'a,
         })
         .into()
 }
+
+/// Distance the cursor has to move with a pressed tab before it's dragged
+const DRAG_THRESHOLD: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct DragState {
+    /// Pressed tab and the cursor position it was pressed at
+    pressed: Option<(usize, Point)>,
+    dragging: bool,
+}
+
+/// Row of tabs that reports tabs dragged to another place
+struct Reorder<'a, Message> {
+    content: Element<'a, Message, Theme>,
+    count: usize,
+    on_reorder: Box<dyn Fn(usize, usize) -> Message + 'a>,
+}
+
+impl<Message> Reorder<'_, Message> {
+    /// Bounds of the reorderable tabs
+    fn tabs(&self, layout: Layout<'_>) -> Vec<Rectangle> {
+        layout
+            .children()
+            .flat_map(|row| row.children())
+            .take(self.count)
+            .map(|tab| tab.bounds())
+            .collect()
+    }
+
+    /// Index the tab dropped at the position goes to
+    fn drop_index(&self, layout: Layout<'_>, position: Point) -> Option<usize> {
+        let tabs = self.tabs(layout);
+        let index = tabs
+            .iter()
+            .position(|bounds| position.x < bounds.x + bounds.width)
+            .unwrap_or(tabs.len());
+        Some(index.min(tabs.len().checked_sub(1)?))
+    }
+}
+
+impl<'a, Message: Clone + 'a> Widget<Message, Theme, Renderer> for Reorder<'a, Message> {
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<DragState>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(DragState::default())
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        let content = self
+            .content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits);
+        layout::Node::with_children(content.size(), vec![content])
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation,
+    ) {
+        self.content.as_widget().operate(
+            &mut tree.children[0],
+            layout.children().next().unwrap(),
+            renderer,
+            operation,
+        );
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        let state = tree.state.downcast_mut::<DragState>();
+        let mut cursor = cursor;
+        let mut dropped = None;
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                *state = DragState::default();
+                if let Some(position) = cursor.position() {
+                    state.pressed = self
+                        .tabs(layout)
+                        .iter()
+                        .position(|bounds| bounds.contains(position))
+                        .map(|index| (index, position));
+                }
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                if let Some((_, origin)) = state.pressed {
+                    if position.distance(origin) > DRAG_THRESHOLD {
+                        state.dragging = true;
+                    }
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if let (Some((from, _)), true) = (state.pressed, state.dragging) {
+                    dropped = cursor
+                        .position()
+                        .and_then(|position| self.drop_index(layout, position))
+                        .map(|to| (from, to));
+                    // Released tab must not be clicked
+                    cursor = mouse::Cursor::Unavailable;
+                }
+                *state = DragState::default();
+            }
+            _ => {}
+        }
+
+        let status = self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout.children().next().unwrap(),
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+
+        match dropped {
+            Some((from, to)) => {
+                if from != to {
+                    shell.publish((self.on_reorder)(from, to));
+                }
+                event::Status::Captured
+            }
+            None => status,
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        if tree.state.downcast_ref::<DragState>().dragging {
+            return mouse::Interaction::Grabbing;
+        }
+
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout.children().next().unwrap(),
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout.children().next().unwrap(),
+            cursor,
+            viewport,
+        );
+    }
+}
+
+impl<'a, Message: Clone + 'a> From<Reorder<'a, Message>> for Element<'a, Message, Theme> {
+    fn from(reorder: Reorder<'a, Message>) -> Self {
+        Element::new(reorder)
+    }
+}